@@ -0,0 +1,192 @@
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// One submission in a batch manifest: a team ID plus the config fields specific to it.
+#[derive(Debug, Clone)]
+pub struct ManifestEntry {
+    pub team_id: String,
+    pub fields: Map<String, Value>,
+}
+
+/// Reads a manifest file. Files ending in `.csv` are parsed as CSV with a header row, all
+/// others as JSON lines. Every entry must have a unique, non-empty `team_id`.
+pub fn read_manifest(path: &Path) -> Result<Vec<ManifestEntry>, String> {
+    let contents = fs::read_to_string(path).map_err(|err| {
+        format!(
+            "The manifest provided ('{}') could not be read: {}",
+            path.display(),
+            err
+        )
+    })?;
+    let is_csv = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+    let entries = if is_csv {
+        parse_csv(&contents)?
+    } else {
+        parse_jsonl(&contents)?
+    };
+
+    let mut seen = HashSet::new();
+    for entry in &entries {
+        if !seen.insert(entry.team_id.clone()) {
+            return Err(format!(
+                "The manifest lists team '{}' more than once.",
+                entry.team_id
+            ));
+        }
+    }
+    Ok(entries)
+}
+
+fn entry_from_fields(mut fields: Map<String, Value>, line: usize) -> Result<ManifestEntry, String> {
+    let team_id = match fields.remove("team_id") {
+        Some(Value::String(id)) if !id.trim().is_empty() => id.trim().to_string(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => return Err(format!("Manifest entry on line {} has no team_id.", line)),
+    };
+    Ok(ManifestEntry { team_id, fields })
+}
+
+/// Config fields that CSV cells are read as numbers for. Every other cell stays a string,
+/// so IDs like `007` keep their leading zeros.
+const NUMERIC_COLUMNS: &[&str] = &[
+    "start_time",
+    "end_time",
+    "size_threshold_kb",
+    "display_threshold",
+];
+
+/// CSV cells are typed loosely: `usernames` is split on `;` or whitespace, cells in
+/// [`NUMERIC_COLUMNS`] become numbers, empty cells are left out so defaults apply.
+fn csv_value(column: &str, cell: &str) -> Value {
+    if column == "usernames" {
+        return Value::Array(
+            cell.split(|c: char| c == ';' || c.is_whitespace())
+                .filter(|name| !name.is_empty())
+                .map(|name| Value::String(name.to_string()))
+                .collect(),
+        );
+    }
+    if NUMERIC_COLUMNS.contains(&column) {
+        if let Ok(number) = cell.parse::<u64>() {
+            return Value::from(number);
+        }
+        if let Ok(number) = cell.parse::<f64>() {
+            return Value::from(number);
+        }
+    }
+    Value::String(cell.to_string())
+}
+
+fn parse_csv(contents: &str) -> Result<Vec<ManifestEntry>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(contents.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|err| format!("The manifest header row is invalid: {}", err))?
+        .clone();
+
+    let mut entries = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let line = index + 2;
+        let record = record.map_err(|err| format!("Manifest line {} is invalid: {}", line, err))?;
+        let mut fields = Map::new();
+        for (column, cell) in headers.iter().zip(record.iter()) {
+            if !cell.is_empty() {
+                fields.insert(column.to_string(), csv_value(column, cell));
+            }
+        }
+        entries.push(entry_from_fields(fields, line)?);
+    }
+    Ok(entries)
+}
+
+fn parse_jsonl(contents: &str) -> Result<Vec<ManifestEntry>, String> {
+    let mut entries = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(fields)) => fields,
+            Ok(_) => return Err(format!("Manifest line {} is not a JSON object.", index + 1)),
+            Err(err) => return Err(format!("Manifest line {} is invalid: {}", index + 1, err)),
+        };
+        entries.push(entry_from_fields(fields, index + 1)?);
+    }
+    Ok(entries)
+}
+
+/// Turns a team ID into a directory name that cannot escape the output directory.
+pub fn directory_name(team_id: &str) -> String {
+    let name: String = team_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_types_cells() {
+        let csv = "team_id,repo,usernames,end_time\n\
+                   t1,https://github.com/a/b,alice;bob,1700000000\n\
+                   t2,https://github.com/c/d,carol,\n";
+        let entries = parse_csv(csv).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].team_id, "t1");
+        assert_eq!(
+            entries[0].fields["usernames"],
+            serde_json::json!(["alice", "bob"])
+        );
+        assert_eq!(
+            entries[0].fields["end_time"],
+            serde_json::json!(1700000000u64)
+        );
+        assert!(!entries[1].fields.contains_key("end_time"));
+    }
+
+    #[test]
+    fn test_parse_csv_keeps_ids_as_strings() {
+        let csv = "team_id,repo,display_threshold\n007,1e3,0.5\n";
+        let entries = parse_csv(csv).unwrap();
+        assert_eq!(entries[0].team_id, "007");
+        assert_eq!(entries[0].fields["repo"], serde_json::json!("1e3"));
+        assert_eq!(
+            entries[0].fields["display_threshold"],
+            serde_json::json!(0.5)
+        );
+    }
+
+    #[test]
+    fn test_parse_jsonl_requires_team_id() {
+        let ok = parse_jsonl("{\"team_id\": \"t1\", \"repo\": \"x\"}\n\n").unwrap();
+        assert_eq!(ok[0].team_id, "t1");
+        assert!(parse_jsonl("{\"repo\": \"x\"}").is_err());
+    }
+
+    #[test]
+    fn test_directory_name_is_sanitized() {
+        assert_eq!(directory_name("../team 7"), "___team_7");
+        assert_eq!(directory_name("team-7_a"), "team-7_a");
+    }
+}
@@ -0,0 +1,259 @@
+pub mod manifest;
+
+use crate::config::profile::{load_fields, merge_fields, resolve_fields};
+use crate::config::validate::{ValidationError, validate};
+use crate::config::{ConfigData, config_from_fields};
+use crate::outcome::Verdict;
+use crate::plag_check::plag_result::PlagiarismVerificationResult;
+use crate::plag_check::policy::PlagiarismVerdict;
+use crate::verify_submission;
+use crate::workspace::WorkdirOptions;
+use manifest::{ManifestEntry, directory_name, read_manifest};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tracing::{Instrument, info, info_span};
+
+/// One row of the batch summary written after every submission has been processed.
+#[derive(Debug, Serialize)]
+pub struct BatchSummaryEntry {
+    pub team_id: String,
+    pub repo: Option<String>,
+    pub output_dir: String,
+    pub verdict: Option<Verdict>,
+    pub metadata_verified: Option<bool>,
+    pub plagiarism_verdict: Option<PlagiarismVerdict>,
+    pub similarity: Option<f64>,
+    pub duplicate_assets: usize,
+    pub github_issues: usize,
+    /// Set when the submission could not be verified at all, e.g. because its config is
+    /// incomplete or its results could not be written.
+    pub error: Option<String>,
+}
+
+fn load_defaults(path: Option<&str>) -> Result<Map<String, Value>, Box<dyn std::error::Error>> {
+    match path {
+        Some(path) => Ok(load_fields(path, "defaults file")?),
+        None => Ok(Map::new()),
+    }
+}
+
+/// Effective config of one team: defaults, then the team's event profile, then its own
+/// fields. Relative paths in the entry are resolved against `manifest_dir`.
+fn config_for_entry(
+    defaults: &Map<String, Value>,
+    entry: &ManifestEntry,
+    manifest_dir: &Path,
+) -> Result<ConfigData, String> {
+    let description = format!("Config for team '{}'", entry.team_id);
+    let mut fields = defaults.clone();
+    merge_fields(
+        &mut fields,
+        &resolve_fields(entry.fields.clone(), manifest_dir)?,
+    );
+    let data = config_from_fields(fields, &description)?;
+    let issues = validate(&data);
+    if !issues.is_empty() {
+        return Err(ValidationError {
+            path: description,
+            issues,
+        }
+        .to_string());
+    }
+    Ok(data)
+}
+
+/// A team ID with its effective config, or why the config could not be built.
+pub type TeamConfig = (String, Result<ConfigData, String>);
+
+/// Effective config of every team in a manifest.
+pub fn manifest_configs(
+    manifest_path: &str,
+    defaults_path: Option<&str>,
+) -> Result<Vec<TeamConfig>, Box<dyn std::error::Error>> {
+    let defaults = load_defaults(defaults_path)?;
+    let manifest_dir = Path::new(manifest_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+    Ok(read_manifest(Path::new(manifest_path))?
+        .iter()
+        .map(|entry| {
+            (
+                entry.team_id.clone(),
+                config_for_entry(&defaults, entry, manifest_dir),
+            )
+        })
+        .collect())
+}
+
+fn summary_entry(
+    entry: &ManifestEntry,
+    data: Option<&ConfigData>,
+    output_dir: &Path,
+) -> BatchSummaryEntry {
+    BatchSummaryEntry {
+        team_id: entry.team_id.clone(),
+        repo: data.map(|d| d.repo.clone()),
+        output_dir: output_dir.display().to_string(),
+        verdict: None,
+        metadata_verified: None,
+        plagiarism_verdict: None,
+        similarity: None,
+        duplicate_assets: 0,
+        github_issues: 0,
+        error: None,
+    }
+}
+
+fn similarity(plagiarism: &PlagiarismVerificationResult) -> Option<f64> {
+    plagiarism.result.score()
+}
+
+fn write_summary(
+    entries: &[BatchSummaryEntry],
+    output_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(
+        output_dir.join("batch_summary.json"),
+        serde_json::to_string_pretty(entries)?,
+    )?;
+
+    let mut writer = csv::Writer::from_path(output_dir.join("batch_summary.csv"))?;
+    writer.write_record([
+        "team_id",
+        "repo",
+        "output_dir",
+        "verdict",
+        "metadata_verified",
+        "plagiarism_verdict",
+        "similarity",
+        "duplicate_assets",
+        "github_issues",
+        "error",
+    ])?;
+    for entry in entries {
+        writer.write_record([
+            entry.team_id.clone(),
+            entry.repo.clone().unwrap_or_default(),
+            entry.output_dir.clone(),
+            entry
+                .verdict
+                .map(|v| format!("{:?}", v))
+                .unwrap_or_default(),
+            entry
+                .metadata_verified
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            entry
+                .plagiarism_verdict
+                .map(|v| format!("{:?}", v))
+                .unwrap_or_default(),
+            entry.similarity.map(|s| s.to_string()).unwrap_or_default(),
+            entry.duplicate_assets.to_string(),
+            entry.github_issues.to_string(),
+            entry.error.clone().unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// One line per verdict with the teams that received it, followed by teams that errored.
+pub fn describe_summary(entries: &[BatchSummaryEntry]) -> String {
+    let mut lines = vec![format!("Verified {} submissions", entries.len())];
+    for verdict in [Verdict::Verified, Verdict::NeedsReview, Verdict::Failed] {
+        let teams: Vec<&str> = entries
+            .iter()
+            .filter(|e| e.error.is_none() && e.verdict == Some(verdict))
+            .map(|e| e.team_id.as_str())
+            .collect();
+        if !teams.is_empty() {
+            lines.push(format!(
+                "  {:?} ({}): {}",
+                verdict,
+                teams.len(),
+                teams.join(", ")
+            ));
+        }
+    }
+    for entry in entries.iter().filter(|e| e.error.is_some()) {
+        lines.push(format!(
+            "  Error ({}): {}",
+            entry.team_id,
+            entry.error.as_deref().unwrap_or("")
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Verifies every submission in `manifest_path` one after another. Each team gets its own
+/// directory under `output_dir`; a failure in one submission is recorded in the summary and
+/// does not stop the batch.
+pub async fn run_batch(
+    manifest_path: &str,
+    defaults_path: Option<&str>,
+    workdir: &WorkdirOptions,
+    output_dir: &Path,
+) -> Result<Vec<BatchSummaryEntry>, Box<dyn std::error::Error>> {
+    let defaults = load_defaults(defaults_path)?;
+    let entries = read_manifest(Path::new(manifest_path))?;
+    let manifest_dir = Path::new(manifest_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+
+    let mut directories = HashSet::new();
+    for entry in &entries {
+        if !directories.insert(directory_name(&entry.team_id)) {
+            return Err(format!(
+                "Team ID '{}' maps to the same output directory as another team.",
+                entry.team_id
+            )
+            .into());
+        }
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let mut summary = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let span = info_span!("team", team_id = %entry.team_id);
+        info!(
+            parent: &span,
+            index = index + 1,
+            total = entries.len(),
+            "Verifying team '{}'",
+            entry.team_id
+        );
+        let team_dir = output_dir.join(directory_name(&entry.team_id));
+        let data = match config_for_entry(&defaults, entry, manifest_dir) {
+            Ok(data) => data,
+            Err(err) => {
+                let mut row = summary_entry(entry, None, &team_dir);
+                row.error = Some(err);
+                summary.push(row);
+                continue;
+            }
+        };
+
+        let mut row = summary_entry(entry, Some(&data), &team_dir);
+        match verify_submission(&data, workdir, &team_dir)
+            .instrument(span.clone())
+            .await
+        {
+            Ok(output) => {
+                row.verdict = Some(output.outcome.verdict);
+                row.metadata_verified = Some(output.metadata.all_verified());
+                row.plagiarism_verdict = Some(output.plagiarism.policy.verdict);
+                row.similarity = similarity(&output.plagiarism);
+                row.duplicate_assets = output.assets.matches.len();
+                row.github_issues = output.github_issues.len();
+            }
+            Err(err) => row.error = Some(err.to_string()),
+        }
+        summary.push(row);
+    }
+
+    write_summary(&summary, output_dir)?;
+    Ok(summary)
+}
@@ -0,0 +1,348 @@
+pub mod profile;
+pub mod time;
+pub mod validate;
+
+use crate::git_tools::bots::is_member;
+use crate::git_tools::new_code::NewCodePolicy;
+use crate::git_tools::prior_work::PriorWork;
+use crate::git_tools::push_times::BulkPushRule;
+use crate::git_tools::team::TeamRules;
+use crate::plag_check::policy::PlagiarismPolicy;
+use crate::webhook::WebhookConfig;
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+use time::{ConfigTime, EventWindow, MemberWindow, RawMemberWindow, parse_time_zone};
+use validate::{ValidationError, validate};
+
+/// Config of a single submission. Times are normalized to Unix seconds while loading; the
+/// file itself may use any format `ConfigTime` accepts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "RawConfigData")]
+pub struct ConfigData {
+    /// Event profile the config was merged onto, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    pub repo: String,
+    pub usernames: Vec<String>,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Windows that replace `start_time`/`end_time` for the commits of some members.
+    pub member_windows: Vec<MemberWindow>,
+    /// IANA time zone used for local times in the file and for displaying the window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    pub size_threshold_kb: u32,
+    pub display_threshold: f32,
+    pub plagiarism_policy: PlagiarismPolicy,
+    /// Starter code handed out by the event: local directories or repository URLs whose
+    /// content copydetect ignores.
+    pub boilerplate: Vec<String>,
+    /// Commit authors that are not team members but are allowed to commit, e.g. bots. Each
+    /// entry is a name or email and may use `*` as a wildcard.
+    pub allowed_bots: Vec<String>,
+    /// Whether the built-in bot allowlist applies in addition to `allowed_bots`.
+    pub default_bots: bool,
+    pub team_rules: TeamRules,
+    pub new_code_policy: NewCodePolicy,
+    /// Code the team disclosed building on; its commits are exempt from the time and
+    /// contributor checks.
+    pub prior_work: Vec<PriorWork>,
+    /// Flags pushes of many commits dated long before them; off if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bulk_push: Option<BulkPushRule>,
+    /// Receives the result of every finished run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<WebhookConfig>,
+    /// Push ledger written by `watch` during the event, checked against the final history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<String>,
+}
+
+fn default_size_threshold() -> u32 {
+    100_000 // ~100MB default
+}
+
+fn default_display_threshold() -> f32 {
+    0.33 //Default 33% similarity
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// `ConfigData` as written in the file, before times are resolved.
+#[derive(Deserialize, JsonSchema)]
+struct RawConfigData {
+    #[serde(default)]
+    event: Option<String>,
+    repo: String,
+    usernames: Vec<String>,
+    start_time: ConfigTime,
+    end_time: ConfigTime,
+    #[serde(default)]
+    member_windows: Vec<RawMemberWindow>,
+    #[serde(default)]
+    time_zone: Option<String>,
+    #[serde(default = "default_size_threshold")]
+    size_threshold_kb: u32,
+    #[serde(default = "default_display_threshold")]
+    display_threshold: f32,
+    #[serde(default)]
+    plagiarism_policy: PlagiarismPolicy,
+    #[serde(default)]
+    boilerplate: Vec<String>,
+    #[serde(default)]
+    allowed_bots: Vec<String>,
+    #[serde(default = "default_true")]
+    default_bots: bool,
+    #[serde(default)]
+    team_rules: TeamRules,
+    #[serde(default)]
+    new_code_policy: NewCodePolicy,
+    #[serde(default)]
+    prior_work: Vec<PriorWork>,
+    #[serde(default)]
+    bulk_push: Option<BulkPushRule>,
+    #[serde(default)]
+    webhook: Option<WebhookConfig>,
+    #[serde(default)]
+    ledger: Option<String>,
+}
+
+impl TryFrom<RawConfigData> for ConfigData {
+    type Error = String;
+
+    fn try_from(raw: RawConfigData) -> Result<Self, Self::Error> {
+        let zone = raw.time_zone.as_deref().map(parse_time_zone).transpose()?;
+        let start_time = raw.start_time.resolve("start_time", zone)?;
+        let end_time = raw.end_time.resolve("end_time", zone)?;
+        let member_windows = raw
+            .member_windows
+            .into_iter()
+            .enumerate()
+            .map(|(index, window)| {
+                window.resolve(
+                    &format!("member_windows[{}]", index),
+                    (start_time, end_time),
+                    zone,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            start_time,
+            end_time,
+            member_windows,
+            time_zone: zone.map(|zone| zone.name().to_string()),
+            event: raw.event,
+            repo: raw.repo,
+            usernames: raw.usernames,
+            size_threshold_kb: raw.size_threshold_kb,
+            display_threshold: raw.display_threshold,
+            plagiarism_policy: raw.plagiarism_policy,
+            boilerplate: raw.boilerplate,
+            allowed_bots: raw.allowed_bots,
+            default_bots: raw.default_bots,
+            team_rules: raw.team_rules,
+            new_code_policy: raw.new_code_policy,
+            prior_work: raw.prior_work,
+            bulk_push: raw.bulk_push,
+            webhook: raw.webhook,
+            ledger: raw.ledger,
+        })
+    }
+}
+
+impl ConfigData {
+    fn zone(&self) -> Option<Tz> {
+        self.time_zone.as_deref().and_then(|name| name.parse().ok())
+    }
+
+    pub fn window(&self) -> EventWindow {
+        EventWindow::new(self.start_time, self.end_time, self.zone())
+    }
+
+    /// The window that applies to a commit author: their member window, or the event window.
+    pub fn window_of(&self, name: &str, email: &str) -> (u64, u64) {
+        self.member_windows
+            .iter()
+            .find(|window| is_member(name, email, &window.usernames))
+            .map_or((self.start_time, self.end_time), |window| {
+                (window.start_time, window.end_time)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// `.toml` and `.yaml`/`.yml` files are read as such; anything else as JSON.
+    fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "JSON",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Yaml => "YAML",
+        }
+    }
+}
+
+/// TOML has a native datetime type, which the rest of the config handling does not know
+/// about. Turn every datetime back into its RFC 3339 text so it parses like a quoted string.
+fn toml_datetimes_to_strings(value: toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Datetime(datetime) => toml::Value::String(datetime.to_string()),
+        toml::Value::Array(items) => {
+            toml::Value::Array(items.into_iter().map(toml_datetimes_to_strings).collect())
+        }
+        toml::Value::Table(table) => toml::Value::Table(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_datetimes_to_strings(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+fn parse_document<T: DeserializeOwned>(contents: &str, format: ConfigFormat) -> Result<T, String> {
+    match format {
+        ConfigFormat::Json => serde_json::from_str(contents).map_err(|err| err.to_string()),
+        ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|err| err.to_string()),
+        ConfigFormat::Toml => {
+            let value: toml::Value = toml::from_str(contents).map_err(|err| err.to_string())?;
+            toml_datetimes_to_strings(value)
+                .try_into()
+                .map_err(|err: toml::de::Error| err.to_string())
+        }
+    }
+}
+
+/// Reads a JSON, TOML, or YAML file. `description` names the file in error messages, e.g.
+/// "config file" or "defaults file".
+pub fn read_file<T: DeserializeOwned>(
+    path: &str,
+    description: &str,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let format = ConfigFormat::from_path(Path::new(path));
+    let contents = fs::read_to_string(path).map_err(|_e| {
+        format!(
+            "The {} provided ('{}') does not exist or could not be read.",
+            description, path
+        )
+    })?;
+    let data = parse_document(&contents, format).map_err(|err| {
+        format!(
+            "The {} provided ('{}') is not a valid {} file: {}",
+            description,
+            path,
+            format.name(),
+            err
+        )
+    })?;
+    Ok(data)
+}
+
+/// Builds a config from already resolved fields (see `profile::resolve_fields`).
+/// `description` names the source in error messages.
+pub fn config_from_fields(
+    fields: Map<String, Value>,
+    description: &str,
+) -> Result<ConfigData, String> {
+    serde_json::from_value(Value::Object(fields))
+        .map_err(|err| format!("{} is invalid: {}", description, err))
+}
+
+/// Reads a config file and merges it onto its event profile, without validating it.
+pub fn read_config(path: &str) -> Result<ConfigData, Box<dyn std::error::Error>> {
+    let fields = profile::load_fields(path, "config file")?;
+    let data = config_from_fields(fields, &format!("The config file provided ('{}')", path))?;
+    Ok(data)
+}
+
+/// Loads a config and rejects it if `validate` finds any problem, so a bad config fails
+/// before anything is cloned.
+pub fn load_config(path: &str) -> Result<ConfigData, Box<dyn std::error::Error>> {
+    let data = read_config(path)?;
+    let issues = validate(&data);
+    if !issues.is_empty() {
+        return Err(Box::new(ValidationError {
+            path: path.to_string(),
+            issues,
+        }));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_config_in_every_format() {
+        let json = r#"{
+            "repo": "https://github.com/a/b",
+            "usernames": ["alice"],
+            "start_time": 1769280135,
+            "end_time": "2026-01-26T06:42:15Z"
+        }"#;
+        let toml = r#"
+            repo = "https://github.com/a/b"
+            usernames = ["alice"]
+            time_zone = "America/New_York"
+            start_time = "2026-01-24 13:42:15"
+            end_time = 2026-01-26T06:42:15Z
+        "#;
+        let yaml = "repo: https://github.com/a/b\nusernames: [alice]\nstart_time: 2026-01-24T13:42:15-05:00\nend_time: 1769409735\n";
+
+        let configs: Vec<ConfigData> = vec![
+            parse_document(json, ConfigFormat::Json).unwrap(),
+            parse_document(toml, ConfigFormat::Toml).unwrap(),
+            parse_document(yaml, ConfigFormat::Yaml).unwrap(),
+        ];
+        for config in &configs {
+            assert_eq!(config.start_time, 1769280135);
+            assert_eq!(config.end_time, 1769409735);
+            assert_eq!(config.display_threshold, 0.33);
+        }
+        assert_eq!(configs[1].window().start, "2026-01-24T13:42:15-05:00");
+        assert_eq!(configs[0].window().time_zone, "UTC");
+    }
+
+    #[test]
+    fn test_time_errors_name_the_field() {
+        let json = r#"{
+            "repo": "https://github.com/a/b",
+            "usernames": ["alice"],
+            "start_time": "2026-01-24 13:42",
+            "end_time": 1769409735
+        }"#;
+        let err = parse_document::<ConfigData>(json, ConfigFormat::Json).unwrap_err();
+        assert!(err.contains("start_time"));
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("event.YML")),
+            ConfigFormat::Yaml
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::config::read_file;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Overlays `overrides` onto `base`. Nested objects are merged key by key, so an entry can
+/// override a single threshold without restating the rest of the policy. Lists are replaced.
+pub fn merge_fields(base: &mut Map<String, Value>, overrides: &Map<String, Value>) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(Value::Object(existing)), Value::Object(nested)) => {
+                merge_fields(existing, nested)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+pub fn is_url(entry: &str) -> bool {
+    entry.starts_with("https://") || entry.starts_with("http://") || entry.starts_with("git@")
+}
+
+/// Makes local `boilerplate` directories and the `ledger` path absolute, relative to the
+/// file that listed them, so they still resolve after being merged into a config from
+/// another directory.
+fn absolutize_paths(fields: &mut Map<String, Value>, base_dir: &Path) {
+    let absolutize = |entry: &mut Value| {
+        if let Value::String(path) = entry
+            && !is_url(path)
+            && Path::new(path).is_relative()
+        {
+            *path = base_dir.join(&*path).display().to_string();
+        }
+    };
+    if let Some(Value::Array(entries)) = fields.get_mut("boilerplate") {
+        entries.iter_mut().for_each(absolutize);
+    }
+    if let Some(ledger) = fields.get_mut("ledger") {
+        absolutize(ledger);
+    }
+    if let Some(Value::Array(entries)) = fields.get_mut("prior_work") {
+        entries
+            .iter_mut()
+            .filter_map(|entry| entry.get_mut("repo"))
+            .for_each(absolutize);
+    }
+}
+
+fn read_object(path: &Path, description: &str) -> Result<Map<String, Value>, String> {
+    let display = path.display().to_string();
+    match read_file::<Value>(&display, description).map_err(|err| err.to_string())? {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(format!(
+            "The {} provided ('{}') is not an object.",
+            description, display
+        )),
+    }
+}
+
+/// Prepares the fields of a config document that lives in `base_dir`: relative paths are
+/// resolved, and if the document names an `event` profile, the profile is loaded and the
+/// document's own fields are merged over it. The resolved profile path is kept in `event`.
+pub fn resolve_fields(
+    mut fields: Map<String, Value>,
+    base_dir: &Path,
+) -> Result<Map<String, Value>, String> {
+    absolutize_paths(&mut fields, base_dir);
+    let event = match fields.remove("event") {
+        None | Some(Value::Null) => return Ok(fields),
+        Some(Value::String(event)) => event,
+        Some(other) => {
+            return Err(format!(
+                "`event` must be the path of an event profile, not {}.",
+                other
+            ));
+        }
+    };
+
+    let event_path: PathBuf = base_dir.join(&event);
+    let mut profile = read_object(&event_path, "event profile")?;
+    if profile.contains_key("event") {
+        return Err(format!(
+            "The event profile '{}' references another event; profiles cannot be nested.",
+            event_path.display()
+        ));
+    }
+    absolutize_paths(
+        &mut profile,
+        event_path.parent().unwrap_or_else(|| Path::new(".")),
+    );
+    merge_fields(&mut profile, &fields);
+    profile.insert(
+        "event".to_string(),
+        Value::String(event_path.display().to_string()),
+    );
+    Ok(profile)
+}
+
+/// Reads a config or defaults file and resolves it with `resolve_fields`.
+pub fn load_fields(path: &str, description: &str) -> Result<Map<String, Value>, String> {
+    let fields = read_object(Path::new(path), description)?;
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    resolve_fields(fields, base_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_merge_fields_merges_nested_objects() {
+        let mut base = serde_json::json!({
+            "start_time": 1,
+            "plagiarism_policy": {"aggregate": {"review_threshold": 0.2, "fail_threshold": 0.5}}
+        });
+        let overrides = serde_json::json!({
+            "start_time": 2,
+            "plagiarism_policy": {"aggregate": {"fail_threshold": 0.7}}
+        });
+        merge_fields(
+            base.as_object_mut().unwrap(),
+            overrides.as_object().unwrap(),
+        );
+        assert_eq!(base["start_time"], 2);
+        assert_eq!(
+            base["plagiarism_policy"]["aggregate"]["review_threshold"],
+            0.2
+        );
+        assert_eq!(
+            base["plagiarism_policy"]["aggregate"]["fail_threshold"],
+            0.7
+        );
+    }
+
+    #[test]
+    fn test_submission_overrides_event_profile() {
+        let dir = std::env::temp_dir().join(format!("profile_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("events")).unwrap();
+        fs::write(
+            dir.join("events/fall.toml"),
+            "start_time = 1\nend_time = 2\ndisplay_threshold = 0.4\nboilerplate = [\"starter\", \"https://github.com/org/template\"]\nledger = \"pushes.jsonl\"\n",
+        )
+        .unwrap();
+        let submission = serde_json::json!({
+            "event": "events/fall.toml",
+            "repo": "https://github.com/a/b",
+            "end_time": 3
+        });
+
+        let fields = resolve_fields(submission.as_object().unwrap().clone(), &dir).unwrap();
+        assert_eq!(fields["start_time"], 1);
+        assert_eq!(fields["end_time"], 3);
+        assert_eq!(fields["display_threshold"], 0.4);
+        assert_eq!(
+            fields["boilerplate"][0],
+            dir.join("events").join("starter").display().to_string()
+        );
+        assert_eq!(fields["boilerplate"][1], "https://github.com/org/template");
+        assert_eq!(
+            fields["ledger"],
+            dir.join("events/pushes.jsonl").display().to_string()
+        );
+        assert!(fields["event"].as_str().unwrap().ends_with("fall.toml"));
+
+        let missing = serde_json::json!({"event": "nope.toml"});
+        assert!(resolve_fields(missing.as_object().unwrap().clone(), &dir).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,254 @@
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Formats accepted for local times, which are interpreted in the config's `time_zone`.
+const LOCAL_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// A point in time as written in a config file: Unix seconds, an RFC 3339 timestamp with an
+/// offset, or a local date and time that needs the config's `time_zone`.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ConfigTime {
+    Unix(i64),
+    Text(String),
+}
+
+impl ConfigTime {
+    /// Converts the time to Unix seconds. `field` names the config field in error messages.
+    pub fn resolve(&self, field: &str, time_zone: Option<Tz>) -> Result<u64, String> {
+        let secs = match self {
+            ConfigTime::Unix(secs) => *secs,
+            ConfigTime::Text(text) => parse_text(field, text.trim(), time_zone)?,
+        };
+        u64::try_from(secs).map_err(|_| format!("`{}` is before 1970-01-01 ({}).", field, secs))
+    }
+}
+
+fn parse_text(field: &str, text: &str, time_zone: Option<Tz>) -> Result<i64, String> {
+    if let Ok(secs) = text.parse::<i64>() {
+        return Ok(secs);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.timestamp());
+    }
+    let naive = LOCAL_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .ok_or_else(|| {
+            format!(
+                "`{}` ('{}') is not Unix seconds, an RFC 3339 timestamp such as \
+                 '2025-09-20T09:00:00-04:00', or a local time such as '2025-09-20 09:00'.",
+                field, text
+            )
+        })?;
+    let Some(zone) = time_zone else {
+        return Err(format!(
+            "`{}` ('{}') has no UTC offset. Add one (e.g. '{}Z') or set `time_zone` to an \
+             IANA name such as 'America/New_York'.",
+            field,
+            text,
+            naive.format("%Y-%m-%dT%H:%M:%S")
+        ));
+    };
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(time) => Ok(time.timestamp()),
+        LocalResult::Ambiguous(earlier, later) => Err(format!(
+            "`{}` ('{}') occurs twice in {} ({} and {}) because of a daylight saving change. \
+             Use an RFC 3339 timestamp with an explicit offset instead.",
+            field,
+            text,
+            zone,
+            earlier.to_rfc3339(),
+            later.to_rfc3339()
+        )),
+        LocalResult::None => Err(format!(
+            "`{}` ('{}') does not exist in {} because of a daylight saving change.",
+            field, text, zone
+        )),
+    }
+}
+
+pub fn parse_time_zone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>().map_err(|_| {
+        format!(
+            "`time_zone` ('{}') is not an IANA time zone name such as 'America/New_York' or 'UTC'.",
+            name
+        )
+    })
+}
+
+/// The normalized event window, echoed in the result so reviewers can check which times the
+/// tool actually used.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventWindow {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub time_zone: String,
+    /// `start_time` and `end_time` as RFC 3339 timestamps in `time_zone`.
+    pub start: String,
+    pub end: String,
+    pub duration: String,
+}
+
+fn format_in_zone(secs: u64, time_zone: Option<Tz>) -> String {
+    let Some(utc) = i64::try_from(secs)
+        .ok()
+        .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+    else {
+        return format!("{} (out of range)", secs);
+    };
+    match time_zone {
+        Some(zone) => utc.with_timezone(&zone).to_rfc3339(),
+        None => utc.to_rfc3339(),
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else {
+        format!("{}h {}m", hours, minutes)
+    }
+}
+
+impl EventWindow {
+    pub fn new(start_time: u64, end_time: u64, time_zone: Option<Tz>) -> Self {
+        Self {
+            start_time,
+            end_time,
+            time_zone: time_zone
+                .map(|zone| zone.name().to_string())
+                .unwrap_or_else(|| "UTC".to_string()),
+            start: format_in_zone(start_time, time_zone),
+            end: format_in_zone(end_time, time_zone),
+            duration: format_duration(end_time.saturating_sub(start_time)),
+        }
+    }
+}
+
+/// A commit window that replaces the event window for some members, e.g. a late arrival,
+/// early hardware access, or an extension.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MemberWindow {
+    pub usernames: Vec<String>,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Why the window differs, cited with the results it applied to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl MemberWindow {
+    /// Names the window in results, e.g. "alice, bob (extension)".
+    pub fn label(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!("{} ({})", self.usernames.join(", "), reason),
+            None => self.usernames.join(", "),
+        }
+    }
+}
+
+/// `MemberWindow` as written in the file. Unset times fall back to the event window.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct RawMemberWindow {
+    usernames: Vec<String>,
+    #[serde(default)]
+    start_time: Option<ConfigTime>,
+    #[serde(default)]
+    end_time: Option<ConfigTime>,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+impl RawMemberWindow {
+    /// `field` names the entry in error messages, e.g. `member_windows[0]`.
+    pub fn resolve(
+        self,
+        field: &str,
+        event: (u64, u64),
+        time_zone: Option<Tz>,
+    ) -> Result<MemberWindow, String> {
+        let resolve = |time: Option<ConfigTime>, name: &str, default: u64| match time {
+            Some(time) => time.resolve(&format!("{}.{}", field, name), time_zone),
+            None => Ok(default),
+        };
+        Ok(MemberWindow {
+            start_time: resolve(self.start_time, "start_time", event.0)?,
+            end_time: resolve(self.end_time, "end_time", event.1)?,
+            usernames: self.usernames,
+            reason: self.reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_accepts_all_formats() {
+        let zone = parse_time_zone("America/New_York").unwrap();
+        assert_eq!(
+            ConfigTime::Unix(1769280135).resolve("start_time", None),
+            Ok(1769280135)
+        );
+        assert_eq!(
+            ConfigTime::Text("2026-01-24T18:42:15Z".to_string()).resolve("start_time", None),
+            Ok(1769280135)
+        );
+        assert_eq!(
+            ConfigTime::Text("2026-01-24 13:42:15".to_string()).resolve("start_time", Some(zone)),
+            Ok(1769280135)
+        );
+        let missing_zone = ConfigTime::Text("2026-01-24 13:42".to_string())
+            .resolve("start_time", None)
+            .unwrap_err();
+        assert!(missing_zone.contains("time_zone"));
+        assert!(ConfigTime::Unix(-5).resolve("end_time", None).is_err());
+    }
+
+    #[test]
+    fn test_ambiguous_local_time_is_rejected() {
+        let zone = parse_time_zone("America/New_York").unwrap();
+        let err = ConfigTime::Text("2025-11-02 01:30".to_string())
+            .resolve("end_time", Some(zone))
+            .unwrap_err();
+        assert!(err.contains("occurs twice"));
+        assert!(parse_time_zone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_member_window_falls_back_to_event_times() {
+        let raw: RawMemberWindow = serde_json::from_value(serde_json::json!({
+            "usernames": ["alice"],
+            "end_time": "2026-01-26T12:00:00Z",
+            "reason": "extension"
+        }))
+        .unwrap();
+        let window = raw
+            .resolve("member_windows[0]", (1769280135, 1769409735), None)
+            .unwrap();
+        assert_eq!(
+            (window.start_time, window.end_time),
+            (1769280135, 1769428800)
+        );
+        assert_eq!(window.label(), "alice (extension)");
+    }
+
+    #[test]
+    fn test_event_window_is_rendered_in_zone() {
+        let zone = parse_time_zone("America/New_York").unwrap();
+        let window = EventWindow::new(1769280135, 1769280135 + 36 * 3600, Some(zone));
+        assert_eq!(window.start, "2026-01-24T13:42:15-05:00");
+        assert_eq!(window.duration, "1d 12h 0m");
+        assert_eq!(window.time_zone, "America/New_York");
+    }
+}
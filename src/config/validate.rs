@@ -0,0 +1,481 @@
+use crate::config::ConfigData;
+use crate::config::profile::is_url;
+use crate::git_tools::prior_work::PriorWork;
+use crate::plag_check::policy::Thresholds;
+use crate::webhook::WebhookConfig;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Unix time of 2100-01-01. Anything later is almost certainly a typo or a millisecond value.
+const FAR_FUTURE_SECS: u64 = 4_102_444_800;
+/// Event windows longer than this are reported, since they make the time checks meaningless.
+const MAX_WINDOW_DAYS: u64 = 366;
+
+/// One problem with a config, pointing at the offending field.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    /// Path of the field, e.g. `usernames[1]` or `plagiarism_policy.per_file.fail_threshold`.
+    pub field: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " ({})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every issue found in a config, reported together so they can be fixed in one pass.
+#[derive(Debug)]
+pub struct ValidationError {
+    pub path: String,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} has {} problem(s):", self.path, self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n  - {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+struct Issues(Vec<ConfigIssue>);
+
+impl Issues {
+    fn push(&mut self, field: impl Into<String>, message: String, suggestion: Option<String>) {
+        self.0.push(ConfigIssue {
+            field: field.into(),
+            message,
+            suggestion,
+        });
+    }
+}
+
+fn check_repo(repo: &str, issues: &mut Issues) {
+    let https = Regex::new(r"^https?://(www\.)?github\.com/([\w.-]+)/([\w.-]+?)(\.git)?/?$")
+        .expect("valid regex");
+    if https.is_match(repo.trim()) {
+        return;
+    }
+    let ssh = Regex::new(r"^git@github\.com:([\w.-]+)/([\w.-]+?)(\.git)?$").expect("valid regex");
+    let suggestion = match ssh.captures(repo.trim()) {
+        Some(captures) => format!(
+            "use the HTTPS URL https://github.com/{}/{}",
+            &captures[1], &captures[2]
+        ),
+        None => "expected https://github.com/<owner>/<repository>".to_string(),
+    };
+    issues.push(
+        "repo",
+        format!("'{}' is not a GitHub repository URL", repo),
+        Some(suggestion),
+    );
+}
+
+fn check_usernames(usernames: &[String], issues: &mut Issues) {
+    if usernames.is_empty() {
+        issues.push(
+            "usernames",
+            "no team members are listed, so every contributor would be unexpected".to_string(),
+            Some("add the GitHub usernames of all team members".to_string()),
+        );
+        return;
+    }
+    // GitHub usernames: up to 39 alphanumerics or single hyphens, not at either end.
+    let valid =
+        Regex::new(r"^[A-Za-z0-9](?:[A-Za-z0-9]|-[A-Za-z0-9]){0,38}$").expect("valid regex");
+    let mut seen = HashSet::new();
+    for (index, username) in usernames.iter().enumerate() {
+        let field = format!("usernames[{}]", index);
+        let trimmed = username.trim().trim_start_matches('@');
+        if !valid.is_match(trimmed) {
+            issues.push(
+                field,
+                format!("'{}' is not a valid GitHub username", username),
+                Some("use the login shown in the profile URL, without '@'".to_string()),
+            );
+        } else if trimmed != username {
+            issues.push(
+                field,
+                format!("'{}' has surrounding whitespace or a leading '@'", username),
+                Some(format!("write it as '{}'", trimmed)),
+            );
+        } else if !seen.insert(username.to_ascii_lowercase()) {
+            issues.push(
+                field,
+                format!("'{}' is listed more than once", username),
+                Some("remove the duplicate".to_string()),
+            );
+        }
+    }
+}
+
+fn far_future_suggestion(secs: u64) -> String {
+    let mut candidate = secs;
+    while candidate > FAR_FUTURE_SECS {
+        candidate /= 10;
+    }
+    format!(
+        "Unix times are in seconds; did you mean {}? RFC 3339 timestamps avoid this mistake",
+        candidate
+    )
+}
+
+fn check_window(config: &ConfigData, issues: &mut Issues) {
+    for (field, secs) in [
+        ("start_time", config.start_time),
+        ("end_time", config.end_time),
+    ] {
+        if secs > FAR_FUTURE_SECS {
+            issues.push(
+                field,
+                format!("{} is after the year 2100", secs),
+                Some(far_future_suggestion(secs)),
+            );
+        }
+    }
+    if config.end_time <= config.start_time {
+        issues.push(
+            "end_time",
+            format!(
+                "the window ends ({}) before or when it starts ({})",
+                config.window().end,
+                config.window().start
+            ),
+            Some("check that start_time and end_time are not swapped".to_string()),
+        );
+    } else if config.end_time - config.start_time > MAX_WINDOW_DAYS * 86_400
+        && config.end_time <= FAR_FUTURE_SECS
+    {
+        issues.push(
+            "end_time",
+            format!(
+                "the window is {} long, longer than {} days",
+                config.window().duration,
+                MAX_WINDOW_DAYS
+            ),
+            Some("check the year of start_time and end_time".to_string()),
+        );
+    }
+}
+
+fn check_member_windows(config: &ConfigData, issues: &mut Issues) {
+    let mut seen = HashSet::new();
+    for (index, window) in config.member_windows.iter().enumerate() {
+        let field = format!("member_windows[{}]", index);
+        if window.usernames.is_empty() {
+            issues.push(
+                format!("{}.usernames", field),
+                "the window applies to nobody".to_string(),
+                Some("list the members it applies to".to_string()),
+            );
+        }
+        for (position, username) in window.usernames.iter().enumerate() {
+            let field = format!("{}.usernames[{}]", field, position);
+            if !config.usernames.contains(username) {
+                issues.push(
+                    field,
+                    format!("'{}' is not one of the team's usernames", username),
+                    None,
+                );
+            } else if !seen.insert(username) {
+                issues.push(
+                    field,
+                    format!("'{}' already has a window", username),
+                    Some("give each member at most one window".to_string()),
+                );
+            }
+        }
+        if window.end_time <= window.start_time {
+            issues.push(
+                format!("{}.end_time", field),
+                format!(
+                    "the window ends ({}) before or when it starts ({})",
+                    window.end_time, window.start_time
+                ),
+                Some("an unset time falls back to the event window".to_string()),
+            );
+        }
+    }
+}
+
+fn check_boilerplate(boilerplate: &[String], issues: &mut Issues) {
+    for (index, entry) in boilerplate.iter().enumerate() {
+        if !is_url(entry) && !Path::new(entry).is_dir() {
+            issues.push(
+                format!("boilerplate[{}]", index),
+                format!("'{}' is not a directory", entry),
+                Some("relative paths are resolved against the file that lists them".to_string()),
+            );
+        }
+    }
+}
+
+fn check_allowed_bots(allowed_bots: &[String], issues: &mut Issues) {
+    for (index, entry) in allowed_bots.iter().enumerate() {
+        if entry.replace('*', "").trim().is_empty() {
+            issues.push(
+                format!("allowed_bots[{}]", index),
+                format!("'{}' would accept every author", entry),
+                Some("name the bot, e.g. 'renovate[bot]'".to_string()),
+            );
+        }
+    }
+}
+
+fn check_prior_work(prior_work: &[PriorWork], issues: &mut Issues) {
+    for (index, entry) in prior_work.iter().enumerate() {
+        match (&entry.repo, &entry.commit) {
+            (Some(_), Some(_)) | (None, None) => issues.push(
+                format!("prior_work[{}]", index),
+                "set exactly one of `commit` and `repo`".to_string(),
+                None,
+            ),
+            (Some(repo), None) if !is_url(repo) && !Path::new(repo).is_dir() => issues.push(
+                format!("prior_work[{}].repo", index),
+                format!("'{}' is neither a URL nor a directory", repo),
+                Some("relative paths are resolved against the file that lists them".to_string()),
+            ),
+            _ => {}
+        }
+    }
+}
+
+fn check_ledger(ledger: &str, issues: &mut Issues) {
+    let parent = Path::new(ledger).parent().unwrap_or_else(|| Path::new(""));
+    if !parent.as_os_str().is_empty() && !parent.is_dir() {
+        issues.push(
+            "ledger",
+            format!("the directory of '{}' does not exist", ledger),
+            Some("relative paths are resolved against the file that lists them".to_string()),
+        );
+    }
+}
+
+fn check_webhook(webhook: &WebhookConfig, issues: &mut Issues) {
+    if !webhook.url.starts_with("https://") && !webhook.url.starts_with("http://") {
+        issues.push(
+            "webhook.url",
+            format!("'{}' is not an HTTP(S) URL", webhook.url),
+            None,
+        );
+    }
+    if webhook.max_attempts == 0 {
+        issues.push(
+            "webhook.max_attempts",
+            "0 attempts means the results are never sent".to_string(),
+            Some("omit the field to use the default of 5".to_string()),
+        );
+    }
+    if webhook.secret().is_none() {
+        issues.push(
+            "webhook.secret_env",
+            format!("the environment variable {} is not set", webhook.secret_env),
+            Some("export the shared secret before running".to_string()),
+        );
+    }
+}
+
+fn check_fraction(field: &str, value: f64, issues: &mut Issues) {
+    if !(0.0..=1.0).contains(&value) {
+        let suggestion = if (1.0..=100.0).contains(&value) {
+            format!(
+                "fractions are used, e.g. {} instead of {}",
+                value / 100.0,
+                value
+            )
+        } else {
+            "use a fraction between 0 and 1".to_string()
+        };
+        issues.push(
+            field,
+            format!("{} is outside 0..1", value),
+            Some(suggestion),
+        );
+    }
+}
+
+fn check_thresholds(field: &str, thresholds: &Thresholds, issues: &mut Issues) {
+    check_fraction(
+        &format!("{}.review_threshold", field),
+        thresholds.review_threshold,
+        issues,
+    );
+    check_fraction(
+        &format!("{}.fail_threshold", field),
+        thresholds.fail_threshold,
+        issues,
+    );
+    if thresholds.review_threshold > thresholds.fail_threshold {
+        issues.push(
+            format!("{}.review_threshold", field),
+            format!(
+                "review threshold {} is above the fail threshold {}",
+                thresholds.review_threshold, thresholds.fail_threshold
+            ),
+            Some("review_threshold must not exceed fail_threshold".to_string()),
+        );
+    }
+}
+
+/// Checks a parsed config for values that would make a run meaningless.
+pub fn validate(config: &ConfigData) -> Vec<ConfigIssue> {
+    let mut issues = Issues(Vec::new());
+    check_repo(&config.repo, &mut issues);
+    check_usernames(&config.usernames, &mut issues);
+    check_window(config, &mut issues);
+    check_member_windows(config, &mut issues);
+    check_fraction(
+        "display_threshold",
+        f64::from(config.display_threshold),
+        &mut issues,
+    );
+    if config.size_threshold_kb == 0 {
+        issues.push(
+            "size_threshold_kb",
+            "0 KB means no comparison repositories are cloned".to_string(),
+            Some("omit the field to use the default of 100000".to_string()),
+        );
+    }
+    check_boilerplate(&config.boilerplate, &mut issues);
+    check_allowed_bots(&config.allowed_bots, &mut issues);
+    check_prior_work(&config.prior_work, &mut issues);
+    if config.team_rules.max_team_size == Some(0) {
+        issues.push(
+            "team_rules.max_team_size",
+            "a limit of 0 members fails every team".to_string(),
+            Some("omit the field to allow any team size".to_string()),
+        );
+    }
+    if let Some(rule) = &config.bulk_push
+        && rule.min_commits == 0
+    {
+        issues.push(
+            "bulk_push.min_commits",
+            "every push has at least 0 commits".to_string(),
+            Some("omit `bulk_push` to turn the check off".to_string()),
+        );
+    }
+    if let Some(ledger) = &config.ledger {
+        check_ledger(ledger, &mut issues);
+    }
+    if let Some(webhook) = &config.webhook {
+        check_webhook(webhook, &mut issues);
+    }
+    let new_code = &config.new_code_policy;
+    for (field, value) in [
+        ("new_code_policy.review_below", new_code.review_below),
+        ("new_code_policy.fail_below", new_code.fail_below),
+    ] {
+        if let Some(value) = value {
+            check_fraction(field, value, &mut issues);
+        }
+    }
+    if let (Some(review), Some(fail)) = (new_code.review_below, new_code.fail_below)
+        && fail > review
+    {
+        issues.push(
+            "new_code_policy.fail_below",
+            format!(
+                "fail threshold {} is above the review threshold {}",
+                fail, review
+            ),
+            Some("fail_below must not exceed review_below".to_string()),
+        );
+    }
+    let policy = &config.plagiarism_policy;
+    check_thresholds(
+        "plagiarism_policy.aggregate",
+        &policy.aggregate,
+        &mut issues,
+    );
+    check_thresholds("plagiarism_policy.per_file", &policy.per_file, &mut issues);
+    issues.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(json: &str) -> ConfigData {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_valid_config_has_no_issues() {
+        let config = config(
+            r#"{"repo": "https://github.com/a/b.git", "usernames": ["alice", "bob-2"],
+                "start_time": 1769280135, "end_time": 1769409735}"#,
+        );
+        assert!(validate(&config).is_empty());
+    }
+
+    #[test]
+    fn test_every_problem_is_reported_with_its_field() {
+        let config = config(
+            r#"{"repo": "git@github.com:a/b.git", "usernames": ["alice", "@bob", "Alice", "-x"],
+                "start_time": 1769280135, "end_time": 175848650400,
+                "display_threshold": 33, "allowed_bots": ["renovate[bot]", "*"],
+                "new_code_policy": {"review_below": 40},
+                "prior_work": [{"commit": "4f2a9c1", "repo": "https://github.com/a/old"}],
+                "member_windows": [{"usernames": ["alice", "carol"], "end_time": 1769280000}],
+                "plagiarism_policy": {"aggregate": {"review_threshold": 0.6, "fail_threshold": 0.5}}}"#,
+        );
+        let issues = validate(&config);
+        let fields: Vec<&str> = issues.iter().map(|i| i.field.as_str()).collect();
+        for field in [
+            "repo",
+            "usernames[1]",
+            "usernames[2]",
+            "usernames[3]",
+            "end_time",
+            "display_threshold",
+            "allowed_bots[1]",
+            "new_code_policy.review_below",
+            "prior_work[0]",
+            "member_windows[0].usernames[1]",
+            "member_windows[0].end_time",
+            "plagiarism_policy.aggregate.review_threshold",
+        ] {
+            assert!(fields.contains(&field), "missing {} in {:?}", field, fields);
+        }
+        let repo = issues.iter().find(|i| i.field == "repo").unwrap();
+        assert!(
+            repo.suggestion
+                .as_deref()
+                .unwrap()
+                .contains("https://github.com/a/b")
+        );
+        let end = issues.iter().find(|i| i.field == "end_time").unwrap();
+        assert!(end.suggestion.as_deref().unwrap().contains("1758486504"));
+    }
+
+    #[test]
+    fn test_reversed_and_empty_window() {
+        let config = config(
+            r#"{"repo": "https://github.com/a/b", "usernames": [],
+                "start_time": 1769409735, "end_time": 1769280135}"#,
+        );
+        let issues = validate(&config);
+        assert!(issues.iter().any(|i| i.field == "usernames"));
+        assert!(
+            issues
+                .iter()
+                .any(|i| i.field == "end_time" && i.message.contains("before"))
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::git_tools::bots::{BotAllowlist, member_of};
+use crate::git_tools::metadata::{AuthorWindow, commit_changes};
+use git2::Repository;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// How a contributor relates to the team.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorRole {
+    Member,
+    Allowlisted,
+    Unregistered,
+}
+
+/// Activity of one contributor on one side of the event window.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Activity {
+    pub commits: usize,
+    /// Lines changed against the first parent; merge commits count none.
+    pub insertions: usize,
+    pub deletions: usize,
+    pub files_touched: usize,
+    /// Unix seconds.
+    pub first_commit: Option<i64>,
+    pub last_commit: Option<i64>,
+    /// Distinct clock hours (UTC) with at least one commit.
+    pub active_hours: usize,
+}
+
+/// Everything one contributor committed, split by author time at the event window, or at
+/// the member window that applies to them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AuthorStats {
+    /// The registered username for members, otherwise the author name.
+    pub name: String,
+    pub role: AuthorRole,
+    pub emails: Vec<String>,
+    pub in_window: Activity,
+    pub outside_window: Activity,
+}
+
+/// `Activity` while it is collected; the sets become counts at the end.
+#[derive(Default)]
+struct Tally {
+    activity: Activity,
+    files: HashSet<String>,
+    hours: HashSet<i64>,
+}
+
+impl Tally {
+    fn finish(mut self) -> Activity {
+        self.activity.files_touched = self.files.len();
+        self.activity.active_hours = self.hours.len();
+        self.activity
+    }
+}
+
+/// Resolves every author of the commits reachable from HEAD, members by username or
+/// GitHub noreply address, and tallies their work inside and outside their member window
+/// from `windows`, or `start..end` (Unix seconds) for everyone else. Sorted by commits
+/// inside the window, most first.
+pub fn author_stats(
+    repo: &Repository,
+    usernames: &[String],
+    bots: &BotAllowlist,
+    windows: &[AuthorWindow],
+    start: u64,
+    end: u64,
+) -> Result<Vec<AuthorStats>, git2::Error> {
+    let event = start as i64..end as i64;
+    let mut authors: Vec<(AuthorStats, Tally, Tally)> = Vec::new();
+
+    // An empty repository has no HEAD and no authors.
+    if repo.head().is_err() {
+        return Ok(Vec::new());
+    }
+    let mut walk = repo.revwalk()?;
+    walk.push_head()?;
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let author = commit.author();
+        let name = author.name().unwrap_or("Unknown");
+        let email = author.email().unwrap_or("");
+        let window = AuthorWindow::of(windows, name, email, event.clone());
+        let (name, role) = match member_of(name, email, usernames) {
+            Some(username) => (username.as_str(), AuthorRole::Member),
+            None if bots.matching(name, email).is_some() => (name, AuthorRole::Allowlisted),
+            None => (name, AuthorRole::Unregistered),
+        };
+
+        let index = match authors.iter().position(|(a, _, _)| a.name == name) {
+            Some(index) => index,
+            None => {
+                let stats = AuthorStats {
+                    name: name.to_string(),
+                    role,
+                    emails: Vec::new(),
+                    in_window: Activity::default(),
+                    outside_window: Activity::default(),
+                };
+                authors.push((stats, Tally::default(), Tally::default()));
+                authors.len() - 1
+            }
+        };
+        let (stats, inside, outside) = &mut authors[index];
+        if !email.is_empty() && !stats.emails.iter().any(|e| e == email) {
+            stats.emails.push(email.to_string());
+        }
+
+        let time = author.when().seconds();
+        let tally = if window.contains(&time) {
+            inside
+        } else {
+            outside
+        };
+        let changes = commit_changes(repo, &commit)?;
+        let activity = &mut tally.activity;
+        activity.commits += 1;
+        activity.insertions += changes.insertions;
+        activity.deletions += changes.deletions;
+        activity.first_commit = Some(activity.first_commit.map_or(time, |t| t.min(time)));
+        activity.last_commit = Some(activity.last_commit.map_or(time, |t| t.max(time)));
+        tally.files.extend(changes.files);
+        tally.hours.insert(time.div_euclid(3600));
+    }
+
+    let mut authors: Vec<AuthorStats> = authors
+        .into_iter()
+        .map(|(mut stats, inside, outside)| {
+            stats.in_window = inside.finish();
+            stats.outside_window = outside.finish();
+            stats
+        })
+        .collect();
+    authors.sort_by(|a, b| {
+        b.in_window
+            .commits
+            .cmp(&a.in_window.commits)
+            .then(a.name.cmp(&b.name))
+    });
+    Ok(authors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::test_support::{END, START, commit};
+
+    #[test]
+    fn test_work_is_split_at_the_window() {
+        let dir = std::env::temp_dir().join(format!("authors_test_{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+        let start = START as i64;
+        commit(&repo, "alice", start - 7200, "old.rs", 40);
+        commit(&repo, "alice", start + 60, "a.rs", 10);
+        commit(&repo, "alice", start + 120, "b.rs", 5);
+        commit(&repo, "alice", start + 4000, "a.rs", 12);
+        commit(&repo, "renovate[bot]", start + 5000, "deps.lock", 3);
+        commit(&repo, "mallory", start + 6000, "c.rs", 1);
+
+        let usernames = vec!["alice".to_string()];
+        // The built-in defaults only accept GitHub's bot emails, so name this one.
+        let bots = BotAllowlist::new(&["renovate[bot]".to_string()], true);
+        let authors = author_stats(&repo, &usernames, &bots, &[], START, END).unwrap();
+        let roles: Vec<(&str, AuthorRole)> =
+            authors.iter().map(|a| (a.name.as_str(), a.role)).collect();
+        assert_eq!(
+            roles,
+            vec![
+                ("alice", AuthorRole::Member),
+                ("mallory", AuthorRole::Unregistered),
+                ("renovate[bot]", AuthorRole::Allowlisted),
+            ]
+        );
+
+        let alice = &authors[0];
+        assert_eq!(
+            alice.in_window,
+            Activity {
+                commits: 3,
+                insertions: 17,
+                deletions: 0,
+                files_touched: 2,
+                first_commit: Some(start + 60),
+                last_commit: Some(start + 4000),
+                active_hours: 2,
+            }
+        );
+        assert_eq!(alice.outside_window.commits, 1);
+        assert_eq!(alice.outside_window.insertions, 40);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_member_window_replaces_the_event_window() {
+        let dir = std::env::temp_dir().join(format!("authors_test_{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+        let start = START as i64;
+        commit(&repo, "alice", start + 60, "a.rs", 10);
+        commit(&repo, "bob", start + 60, "b.rs", 10);
+        commit(&repo, "bob", start + 7200, "c.rs", 10);
+
+        let usernames = vec!["alice".to_string(), "bob".to_string()];
+        let secs = |secs: i64| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs as u64);
+        let windows = vec![AuthorWindow {
+            label: "bob (late arrival)".to_string(),
+            usernames: vec!["bob".to_string()],
+            range: secs(start + 3600)..secs(END as i64),
+        }];
+        let bots = BotAllowlist::default();
+        let authors = author_stats(&repo, &usernames, &bots, &windows, START, END).unwrap();
+        let counts: Vec<(&str, usize, usize)> = authors
+            .iter()
+            .map(|a| {
+                (
+                    a.name.as_str(),
+                    a.in_window.commits,
+                    a.outside_window.commits,
+                )
+            })
+            .collect();
+        assert_eq!(counts, vec![("alice", 1, 0), ("bob", 1, 1)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Accepted unless a config sets `default_bots = false`: GitHub Apps such as
+/// `dependabot[bot]`, `github-actions[bot]`, or app-builder bots, and commits GitHub makes
+/// itself, e.g. when a pull request is merged in the web UI. These only match the email
+/// GitHub assigns, since anyone can set their author name to `x[bot]`.
+pub const DEFAULT_BOTS: &[&str] = &["*[bot]@users.noreply.github.com", "noreply@github.com"];
+
+/// Matches a whole text case-insensitively, `*` standing for any run of characters.
+pub fn wildcard(pattern: &str) -> Regex {
+    let pattern = format!("(?i)^{}$", regex::escape(pattern).replace(r"\*", ".*"));
+    Regex::new(&pattern).expect("escaped pattern is valid")
+}
+
+/// Commit authors that may appear besides the team. Configured entries match an author name
+/// or email, built-in ones only the email; both case-insensitively, with `*` standing for any
+/// run of characters.
+#[derive(Debug, Clone)]
+pub struct BotAllowlist {
+    /// Entry, its pattern, and whether it may match the author name.
+    entries: Vec<(String, Regex, bool)>,
+}
+
+impl Default for BotAllowlist {
+    fn default() -> Self {
+        Self::new(&[], true)
+    }
+}
+
+impl BotAllowlist {
+    pub fn new(entries: &[String], defaults: bool) -> Self {
+        let defaults = DEFAULT_BOTS
+            .iter()
+            .filter(|_| defaults)
+            .map(|e| (e.to_string(), false));
+        let entries = entries
+            .iter()
+            .map(|e| (e.clone(), true))
+            .chain(defaults)
+            .map(|(entry, by_name)| {
+                let regex = wildcard(&entry);
+                (entry, regex, by_name)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// The first entry accepting an author with this name and email.
+    pub fn matching(&self, name: &str, email: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(_, regex, by_name)| (*by_name && regex.is_match(name)) || regex.is_match(email))
+            .map(|(entry, _, _)| entry.as_str())
+    }
+}
+
+/// The GitHub login in a noreply address, `[<id>+]<login>@users.noreply.github.com`.
+fn noreply_login(email: &str) -> Option<&str> {
+    let local = email
+        .strip_suffix("@users.noreply.github.com")
+        .filter(|local| !local.is_empty())?;
+    Some(local.split_once('+').map_or(local, |(_, login)| login))
+}
+
+/// The team member who authored a commit: by name, or by the GitHub noreply address that
+/// the web UI commits with when the display name differs from the login.
+pub fn member_of<'a>(name: &str, email: &str, usernames: &'a [String]) -> Option<&'a String> {
+    let login = noreply_login(email);
+    usernames
+        .iter()
+        .find(|user| *user == name || login.is_some_and(|login| login.eq_ignore_ascii_case(user)))
+}
+
+pub fn is_member(name: &str, email: &str, usernames: &[String]) -> bool {
+    member_of(name, email, usernames).is_some()
+}
+
+/// Commits of one allowlisted author, so organizers see how much a bot contributed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AllowlistedAuthor {
+    pub name: String,
+    pub email: String,
+    /// The allowlist entry that accepted the author.
+    pub rule: String,
+    pub commits: usize,
+    /// Lines changed against the first parent; merge commits count none, since their
+    /// changes are counted with the merged commits.
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_and_configured_entries_match() {
+        let allowlist = BotAllowlist::new(&["*@users.noreply.replit.com".to_string()], true);
+        assert_eq!(
+            allowlist.matching(
+                "dependabot[bot]",
+                "49699333+dependabot[bot]@users.noreply.github.com"
+            ),
+            Some("*[bot]@users.noreply.github.com")
+        );
+        // A person calling themselves a bot is not accepted by the defaults.
+        assert_eq!(allowlist.matching("x[bot]", "x@example.com"), None);
+        assert_eq!(allowlist.matching("web-flow", "x@example.com"), None);
+        assert_eq!(
+            allowlist.matching("GitHub", "noreply@github.com"),
+            Some("noreply@github.com")
+        );
+        assert_eq!(
+            allowlist.matching("Agent", "agent@Users.Noreply.Replit.com"),
+            Some("*@users.noreply.replit.com")
+        );
+        assert_eq!(allowlist.matching("mallory", "mallory@example.com"), None);
+        assert_eq!(
+            BotAllowlist::new(&[], false).matching("dependabot[bot]", ""),
+            None
+        );
+    }
+
+    #[test]
+    fn test_noreply_address_identifies_member() {
+        let usernames = vec!["alice".to_string()];
+        assert!(is_member("alice", "", &usernames));
+        assert!(is_member(
+            "Alice Smith",
+            "123+Alice@users.noreply.github.com",
+            &usernames
+        ));
+        assert!(is_member(
+            "Alice Smith",
+            "alice@users.noreply.github.com",
+            &usernames
+        ));
+        assert!(!is_member("Alice Smith", "alice@example.com", &usernames));
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::{DateTime, Utc};
+use octocrab::Octocrab;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// GitHub serves at most 300 events per repository, 100 per page.
+const EVENT_PAGES: u32 = 3;
+const EVENTS_PER_PAGE: usize = 100;
+
+/// Client for the GitHub API. `GITHUB_TOKEN` or `GH_TOKEN` authenticates it, which raises
+/// the rate limit; `GITHUB_API_URL` points it at another server, e.g. GitHub Enterprise or
+/// a local mock.
+pub fn client() -> Result<Octocrab, Box<dyn std::error::Error>> {
+    let mut builder = Octocrab::builder();
+    if let Some(token) = ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|token| !token.is_empty())
+    {
+        builder = builder.personal_token(token);
+    }
+    if let Ok(url) = std::env::var("GITHUB_API_URL")
+        && !url.is_empty()
+    {
+        builder = builder.base_uri(url)?;
+    }
+    Ok(builder.build()?)
+}
+
+/// A push as GitHub received it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Push {
+    pub branch: String,
+    /// Branch head before the push; all zeros for a new branch.
+    pub before: String,
+    pub head: String,
+    /// Unix seconds.
+    pub pushed_at: i64,
+}
+
+/// The parts of a repository's recent events the checks use.
+#[derive(Debug, Clone, Default)]
+pub struct RepoEvents {
+    /// Oldest first.
+    pub pushes: Vec<Push>,
+    /// Unix seconds of the most recent `PublicEvent`.
+    pub made_public_at: Option<i64>,
+}
+
+/// Events are read as plain JSON so payloads GitHub has trimmed or extended still parse.
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    payload: Value,
+}
+
+#[derive(Deserialize)]
+struct RawPushPayload {
+    #[serde(rename = "ref")]
+    reference: String,
+    before: String,
+    head: String,
+}
+
+fn parse_events(events: Vec<RawEvent>) -> RepoEvents {
+    let mut result = RepoEvents::default();
+    for event in events {
+        let time = event.created_at.timestamp();
+        match event.kind.as_str() {
+            "PushEvent" => {
+                let Ok(payload) = serde_json::from_value::<RawPushPayload>(event.payload) else {
+                    continue;
+                };
+                let Some(branch) = payload.reference.strip_prefix("refs/heads/") else {
+                    continue;
+                };
+                result.pushes.push(Push {
+                    branch: branch.to_string(),
+                    before: payload.before,
+                    head: payload.head,
+                    pushed_at: time,
+                });
+            }
+            "PublicEvent" => {
+                result.made_public_at = result.made_public_at.max(Some(time));
+            }
+            _ => {}
+        }
+    }
+    result.pushes.sort_by_key(|push| push.pushed_at);
+    result
+}
+
+/// Recent events of `owner/name`. GitHub only keeps the last 90 days and 300 events.
+pub async fn fetch_events(
+    octocrab: &Octocrab,
+    owner: &str,
+    name: &str,
+) -> octocrab::Result<RepoEvents> {
+    let route = format!("/repos/{}/{}/events", owner, name);
+    let mut events = Vec::new();
+    for page in 1..=EVENT_PAGES {
+        let batch: Vec<RawEvent> = octocrab
+            .get(
+                &route,
+                Some(&[("per_page", EVENTS_PER_PAGE as u32), ("page", page)]),
+            )
+            .await?;
+        let last = batch.len() < EVENTS_PER_PAGE;
+        events.extend(batch);
+        if last {
+            break;
+        }
+    }
+    Ok(parse_events(events))
+}
+
+#[cfg(test)]
+pub mod mock {
+    use axum::Json;
+    use axum::Router;
+    use axum::routing::get;
+    use octocrab::Octocrab;
+    use serde_json::Value;
+
+    /// Serves `repo` and `events` for every repository, the way the GitHub API would.
+    pub async fn github(repo: Value, events: Value) -> Octocrab {
+        let app = Router::new()
+            .route(
+                "/repos/{owner}/{name}",
+                get(move || {
+                    let repo = repo.clone();
+                    async move { Json(repo) }
+                }),
+            )
+            .route(
+                "/repos/{owner}/{name}/events",
+                get(move || {
+                    let events = events.clone();
+                    async move { Json(events) }
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Octocrab::builder().base_uri(url).unwrap().build().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_push_and_public_events_are_read() {
+        let events = json!([
+            {"type": "PushEvent", "created_at": "2026-01-26T08:00:00Z",
+             "payload": {"ref": "refs/heads/main", "before": "b2", "head": "h2", "size": 3}},
+            {"type": "PublicEvent", "created_at": "2026-01-25T12:00:00Z", "payload": {}},
+            {"type": "PushEvent", "created_at": "2026-01-25T10:00:00Z",
+             "payload": {"ref": "refs/heads/main", "before": "b1", "head": "h1"}},
+            {"type": "PushEvent", "created_at": "2026-01-25T11:00:00Z",
+             "payload": {"ref": "refs/tags/v1", "before": "0", "head": "t"}},
+            {"type": "WatchEvent", "created_at": "2026-01-25T13:00:00Z", "payload": {}}
+        ]);
+        let octocrab = mock::github(json!({}), events).await;
+        let events = fetch_events(&octocrab, "team", "sub").await.unwrap();
+        let heads: Vec<&str> = events.pushes.iter().map(|p| p.head.as_str()).collect();
+        assert_eq!(heads, vec!["h1", "h2"]);
+        assert_eq!(events.pushes[1].branch, "main");
+        assert_eq!(events.made_public_at, Some(1_769_342_400));
+    }
+}
@@ -0,0 +1,186 @@
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What went wrong while talking to GitHub or cloning, so consumers do not have to parse
+/// the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GithubIssueKind {
+    UserNotFound,
+    /// GitHub asks for credentials, which it also does for repositories that do not exist.
+    RepoPrivate,
+    RepoNotFound,
+    RateLimited,
+    CloneFailed,
+    Network,
+    /// Any other error response from the GitHub API.
+    ApiError,
+    /// A local directory named in the config does not exist.
+    PathNotFound,
+}
+
+/// A problem that kept part of the verification from running, e.g. a member whose
+/// repositories could not be listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GithubIssue {
+    pub kind: GithubIssueKind,
+    /// The username, repository URL, or path the issue is about.
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    /// Whether running again later may succeed, e.g. after a rate limit resets.
+    pub retryable: bool,
+    pub message: String,
+}
+
+impl fmt::Display for GithubIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+fn is_rate_limit(status: u16, message: &str) -> bool {
+    status == 429 || (status == 403 && message.to_ascii_lowercase().contains("rate limit"))
+}
+
+/// git2 only reports HTTP failures in its message, e.g. "unexpected http status code: 404".
+fn status_in_message(message: &str) -> Option<u16> {
+    let pattern = Regex::new(r"(?i)status code:? (\d{3})").expect("valid regex");
+    pattern
+        .captures(message)
+        .and_then(|captures| captures[1].parse().ok())
+}
+
+impl GithubIssue {
+    /// A GitHub API call about `subject` failed; a 404 is reported as `not_found`.
+    fn api_failed(
+        subject: &str,
+        not_found: GithubIssueKind,
+        err: &octocrab::Error,
+        message: String,
+    ) -> Self {
+        let (kind, http_status, retryable) = match err {
+            octocrab::Error::GitHub { source, .. } => {
+                let status = source.status_code.as_u16();
+                if status == 404 {
+                    (not_found, Some(status), false)
+                } else if is_rate_limit(status, &source.message) {
+                    (GithubIssueKind::RateLimited, Some(status), true)
+                } else {
+                    (GithubIssueKind::ApiError, Some(status), status >= 500)
+                }
+            }
+            octocrab::Error::Hyper { .. }
+            | octocrab::Error::Service { .. }
+            | octocrab::Error::Http { .. } => (GithubIssueKind::Network, None, true),
+            _ => (GithubIssueKind::ApiError, None, false),
+        };
+        Self {
+            kind,
+            subject: subject.to_string(),
+            http_status,
+            retryable,
+            message,
+        }
+    }
+
+    /// Listing the repositories of `user` through the GitHub API failed.
+    pub fn listing_failed(user: &str, err: &octocrab::Error) -> Self {
+        let message = format!("Failed to list repos for user '{}': {}", user, err);
+        Self::api_failed(user, GithubIssueKind::UserNotFound, err, message)
+    }
+
+    /// Looking up the repository at `url` through the GitHub API failed. GitHub answers 404
+    /// for private repositories too.
+    pub fn lookup_failed(url: &str, err: &octocrab::Error) -> Self {
+        let message = format!("Failed to look up repo '{}': {}", url, err);
+        Self::api_failed(url, GithubIssueKind::RepoNotFound, err, message)
+    }
+
+    /// Cloning `url` failed. `what` describes the repository in the message, e.g.
+    /// "main repo" or "boilerplate repo".
+    pub fn clone_failed(what: &str, url: &str, err: &git2::Error) -> Self {
+        let message = format!("Failed to clone {} '{}': {}", what, url, err);
+        let http_status = status_in_message(err.message());
+        let (kind, retryable) = match (err.code(), err.class(), http_status) {
+            (git2::ErrorCode::Auth, _, _) | (_, _, Some(401 | 403)) => {
+                (GithubIssueKind::RepoPrivate, false)
+            }
+            (_, _, Some(404)) => (GithubIssueKind::RepoNotFound, false),
+            (_, _, Some(429)) => (GithubIssueKind::RateLimited, true),
+            (_, _, Some(status)) if status >= 500 => (GithubIssueKind::CloneFailed, true),
+            (_, git2::ErrorClass::Net | git2::ErrorClass::Ssl, None) => {
+                (GithubIssueKind::Network, true)
+            }
+            _ => (GithubIssueKind::CloneFailed, false),
+        };
+        Self {
+            kind,
+            subject: url.to_string(),
+            http_status,
+            retryable,
+            message,
+        }
+    }
+
+    pub fn path_not_found(what: &str, path: &str) -> Self {
+        Self {
+            kind: GithubIssueKind::PathNotFound,
+            subject: path.to_string(),
+            http_status: None,
+            retryable: false,
+            message: format!("{} '{}' does not exist", what, path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_errors_are_classified() {
+        let not_found = git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Http,
+            "unexpected http status code: 404",
+        );
+        let issue = GithubIssue::clone_failed("repo", "https://github.com/a/b", &not_found);
+        assert_eq!(issue.kind, GithubIssueKind::RepoNotFound);
+        assert_eq!(issue.http_status, Some(404));
+        assert!(!issue.retryable);
+        assert_eq!(issue.subject, "https://github.com/a/b");
+
+        let auth = git2::Error::new(
+            git2::ErrorCode::Auth,
+            git2::ErrorClass::Http,
+            "remote authentication required but no callback set",
+        );
+        let issue = GithubIssue::clone_failed("repo", "https://github.com/a/c", &auth);
+        assert_eq!(issue.kind, GithubIssueKind::RepoPrivate);
+
+        let network = git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Net,
+            "failed to resolve address for github.com",
+        );
+        let issue = GithubIssue::clone_failed("repo", "https://github.com/a/d", &network);
+        assert_eq!(issue.kind, GithubIssueKind::Network);
+        assert!(issue.retryable);
+        assert!(issue.message.starts_with("Failed to clone repo"));
+    }
+
+    #[test]
+    fn test_issue_serializes_kind_in_snake_case() {
+        let issue = GithubIssue::path_not_found("Boilerplate directory", "starter");
+        let value = serde_json::to_value(&issue).unwrap();
+        assert_eq!(value["kind"], "path_not_found");
+        assert!(value.get("http_status").is_none());
+        assert_eq!(
+            issue.to_string(),
+            "Boilerplate directory 'starter' does not exist"
+        );
+    }
+}
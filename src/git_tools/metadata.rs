@@ -1,15 +1,56 @@
-use crate::git_tools::verification::{FailureReason, VerificationResult};
-use git2::{Repository, Sort, Time as GitTime};
-use serde::Serialize;
+use crate::git_tools::authors::AuthorStats;
+use crate::git_tools::bots::{AllowlistedAuthor, BotAllowlist, is_member, member_of};
+use crate::git_tools::new_code::NewCodeResult;
+use crate::git_tools::prior_work::PriorWorkResult;
+use crate::git_tools::provenance::ProvenanceResult;
+use crate::git_tools::push_times::PushTimeCheck;
+use crate::git_tools::team::TeamCheck;
+use crate::git_tools::verification::{FailureReason, VerificationResult, unix_secs};
+use crate::ledger::LedgerCheck;
+use git2::{Commit, Oid, Repository, Revwalk, Sort, Time as GitTime};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::ops::Range;
-use std::path::Path;
 use std::time::{Duration, SystemTime};
 
+/// A commit window that replaces the event window for the commits of some members.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorWindow {
+    /// Cited in results, e.g. "alice (late arrival)".
+    pub label: String,
+    pub usernames: Vec<String>,
+    pub range: Range<SystemTime>,
+}
+
+impl AuthorWindow {
+    /// The window an author's commits are measured against, in Unix seconds: the first of
+    /// `windows` that lists them, or `default`.
+    pub fn of(
+        windows: &[AuthorWindow],
+        name: &str,
+        email: &str,
+        default: Range<i64>,
+    ) -> Range<i64> {
+        windows
+            .iter()
+            .find(|window| is_member(name, email, &window.usernames))
+            .map_or(default, |window| {
+                unix_secs(&window.range.start)..unix_secs(&window.range.end)
+            })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MetadataConstraints {
     pub first_commit_time: Option<Range<SystemTime>>,
     pub last_commit_time: Option<Range<SystemTime>>,
     pub usernames: Option<Vec<String>>,
+    /// Authors accepted besides `usernames`.
+    pub bots: BotAllowlist,
+    /// Declared prior work; these commits and their ancestors are left out of the checks.
+    pub prior_work: Vec<Oid>,
+    /// Member windows; everyone else's commits are checked against the two ranges above.
+    pub windows: Vec<AuthorWindow>,
 }
 
 impl MetadataConstraints {
@@ -18,6 +59,9 @@ impl MetadataConstraints {
             first_commit_time: None,
             last_commit_time: None,
             usernames: None,
+            bots: BotAllowlist::default(),
+            prior_work: Vec::new(),
+            windows: Vec::new(),
         }
     }
 
@@ -30,15 +74,72 @@ impl MetadataConstraints {
             first_commit_time,
             last_commit_time,
             usernames,
+            bots: BotAllowlist::default(),
+            prior_work: Vec::new(),
+            windows: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+/// One commit reachable from HEAD, as listed in the verification report.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CommitSummary {
+    pub id: String,
+    pub author: String,
+    pub email: String,
+    /// Author time in Unix seconds.
+    pub time: i64,
+    pub summary: String,
+}
+
+/// The commit times of the authors one window applied to.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WindowCheck {
+    /// `event` for the shared window, otherwise the member window's label.
+    pub window: String,
+    /// Unix seconds.
+    pub start_time: i64,
+    pub end_time: i64,
+    pub authors: Vec<String>,
+    pub first_commit: i64,
+    pub last_commit: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct MetadataVerificationResult {
     pub first_commit_time: VerificationResult,
     pub last_commit_time: VerificationResult,
     pub contributors: VerificationResult,
+    #[serde(default)]
+    pub commits: Vec<CommitSummary>,
+    /// Which window each author's commits were checked against, if the config has member
+    /// windows.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<WindowCheck>,
+    /// Authors outside the team that the bot allowlist accepted, with what they committed.
+    #[serde(default)]
+    pub allowlisted: Vec<AllowlistedAuthor>,
+    /// Work of every contributor inside and outside the event window.
+    #[serde(default)]
+    pub authors: Vec<AuthorStats>,
+    /// The submission compared with the prior work the config declares, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_work: Option<PriorWorkResult>,
+    /// Share of the deadline tree's lines written during the event, by blame.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_code: Option<NewCodeResult>,
+    /// Per-member statistics and team rule findings, if the repository could be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<TeamCheck>,
+    /// Checks on the repository's GitHub metadata rather than its commits.
+    #[serde(default)]
+    pub provenance: ProvenanceResult,
+    /// Comparison with the push ledger, if the config names one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push_timeline: Option<LedgerCheck>,
+    /// Comparison with the pushes GitHub recorded, if they could be fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub push_times: Option<PushTimeCheck>,
 }
 
 impl MetadataVerificationResult {
@@ -51,6 +152,16 @@ impl MetadataVerificationResult {
             first_commit_time: first,
             last_commit_time: last,
             contributors,
+            commits: Vec::new(),
+            windows: Vec::new(),
+            allowlisted: Vec::new(),
+            authors: Vec::new(),
+            new_code: None,
+            prior_work: None,
+            team: None,
+            provenance: ProvenanceResult::default(),
+            push_timeline: None,
+            push_times: None,
         }
     }
 
@@ -70,41 +181,130 @@ impl MetadataVerificationResult {
     }
 }
 
-fn get_repository_contributors(repo: &Repository) -> Result<Vec<String>, git2::Error> {
+/// Authors of the commits reachable from HEAD that are neither team members nor
+/// allowlisted, and the allowlisted ones with their commits.
+struct ContributorCheck {
+    unauthorized: Vec<String>,
+    allowlisted: Vec<AllowlistedAuthor>,
+}
+
+/// What a commit changed against its first parent. Merges change nothing, since their
+/// changes are counted with the merged commits.
+#[derive(Debug, Default)]
+pub struct CommitChanges {
+    pub insertions: usize,
+    pub deletions: usize,
+    pub files: Vec<String>,
+}
+
+pub fn commit_changes(repo: &Repository, commit: &Commit) -> Result<CommitChanges, git2::Error> {
+    if commit.parent_count() > 1 {
+        return Ok(CommitChanges::default());
+    }
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+    let stats = diff.stats()?;
+    let files = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    Ok(CommitChanges {
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+        files,
+    })
+}
+
+/// Commits reachable from HEAD, without `prior_work` and its ancestors.
+fn walk_own_commits<'r>(
+    repo: &'r Repository,
+    prior_work: &[Oid],
+) -> Result<Revwalk<'r>, git2::Error> {
     let mut walk = repo.revwalk()?;
     walk.push_head()?;
+    for oid in prior_work {
+        walk.hide(*oid)?;
+    }
+    Ok(walk)
+}
 
-    let mut contributors = std::collections::HashSet::new();
+fn check_contributors(
+    repo: &Repository,
+    usernames: &[String],
+    bots: &BotAllowlist,
+    prior_work: &[Oid],
+) -> Result<ContributorCheck, git2::Error> {
+    let walk = walk_own_commits(repo, prior_work)?;
 
+    let mut unauthorized = std::collections::BTreeSet::new();
+    let mut allowlisted: Vec<AllowlistedAuthor> = Vec::new();
     for oid in walk {
-        let oid = oid?;
-        let commit = repo.find_commit(oid)?;
+        let commit = repo.find_commit(oid?)?;
         let author = commit.author();
-        let author_name = author.name().unwrap_or("Unknown").to_string();
-        contributors.insert(author_name);
+        let name = author.name().unwrap_or("Unknown");
+        let email = author.email().unwrap_or("");
+        if is_member(name, email, usernames) {
+            continue;
+        }
+        let Some(rule) = bots.matching(name, email) else {
+            unauthorized.insert(name.to_string());
+            continue;
+        };
+        let changes = commit_changes(repo, &commit)?;
+        let index = match allowlisted
+            .iter()
+            .position(|a| a.name == name && a.email == email)
+        {
+            Some(index) => index,
+            None => {
+                allowlisted.push(AllowlistedAuthor {
+                    name: name.to_string(),
+                    email: email.to_string(),
+                    rule: rule.to_string(),
+                    commits: 0,
+                    insertions: 0,
+                    deletions: 0,
+                });
+                allowlisted.len() - 1
+            }
+        };
+        let entry = &mut allowlisted[index];
+        entry.commits += 1;
+        entry.insertions += changes.insertions;
+        entry.deletions += changes.deletions;
     }
+    allowlisted.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
 
-    Ok(contributors.into_iter().collect())
+    Ok(ContributorCheck {
+        unauthorized: unauthorized.into_iter().collect(),
+        allowlisted,
+    })
 }
 
-fn verify_contributors(
-    actual_contributors: &[String],
-    expected_usernames: &[String],
-) -> VerificationResult {
-    let actual_set: std::collections::HashSet<String> =
-        actual_contributors.iter().cloned().collect();
-    let expected_set: std::collections::HashSet<String> =
-        expected_usernames.iter().cloned().collect();
-
-    let unauthorized_users: Vec<String> = actual_set.difference(&expected_set).cloned().collect();
+/// Lists commits reachable from HEAD, newest first.
+pub fn list_commits(repo: &Repository) -> Result<Vec<CommitSummary>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TIME)?;
+    walk.push_head()?;
 
-    if unauthorized_users.is_empty() {
-        VerificationResult::Verified
-    } else {
-        VerificationResult::Failed(FailureReason::AdditionalUnauthorizedUsers(
-            unauthorized_users,
-        ))
+    let mut commits = Vec::new();
+    for oid in walk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        let author = commit.author();
+        commits.push(CommitSummary {
+            id: oid.to_string(),
+            author: author.name().unwrap_or("Unknown").to_string(),
+            email: author.email().unwrap_or("").to_string(),
+            time: author.when().seconds(),
+            summary: commit.summary().unwrap_or("").to_string(),
+        });
     }
+    Ok(commits)
 }
 
 fn git_time_to_system_time(t: GitTime) -> SystemTime {
@@ -116,11 +316,10 @@ fn git_time_to_system_time(t: GitTime) -> SystemTime {
     }
 }
 
-fn earliest_commit_time(repo: &Repository) -> Result<SystemTime, git2::Error> {
-    let mut walk = repo.revwalk()?;
+fn earliest_commit_time(repo: &Repository, prior_work: &[Oid]) -> Result<SystemTime, git2::Error> {
+    let mut walk = walk_own_commits(repo, prior_work)?;
     // Walk commits by time to be efficient; still keep a min to be robust
     walk.set_sorting(Sort::TIME | Sort::REVERSE)?;
-    walk.push_head()?;
 
     let mut earliest: Option<SystemTime> = None;
     for oid in walk {
@@ -142,11 +341,10 @@ fn earliest_commit_time(repo: &Repository) -> Result<SystemTime, git2::Error> {
     earliest.ok_or_else(|| git2::Error::from_str("repository has no commits"))
 }
 
-fn latest_commit_time(repo: &Repository) -> Result<SystemTime, git2::Error> {
-    let mut walk = repo.revwalk()?;
+fn latest_commit_time(repo: &Repository, prior_work: &[Oid]) -> Result<SystemTime, git2::Error> {
+    let mut walk = walk_own_commits(repo, prior_work)?;
     // Walk commits by time to be efficient; still keep a max to be robust
     walk.set_sorting(Sort::TIME)?;
-    walk.push_head()?;
 
     let mut latest: Option<SystemTime> = None;
     for oid in walk {
@@ -187,48 +385,177 @@ fn verify_time(
     }
 }
 
-pub fn check_metadata_at_path<P: AsRef<Path>>(
-    path: P,
-    constraints: MetadataConstraints,
-) -> MetadataVerificationResult {
-    match Repository::open(path) {
-        Ok(repo) => check_metadata(&repo, constraints),
-        Err(e) => MetadataVerificationResult::new(
-            VerificationResult::Failed(FailureReason::GitError(e)),
-            VerificationResult::Failed(FailureReason::GitError(git2::Error::from_str(
-                "failed to open repository (see first error)",
-            ))),
-            VerificationResult::Failed(FailureReason::GitError(git2::Error::from_str(
-                "failed to open repository (see first error)",
-            ))),
-        ),
+/// Authors and commit time span of the commits checked against one window.
+struct WindowSpan {
+    authors: Vec<String>,
+    first: SystemTime,
+    last: SystemTime,
+}
+
+/// Splits the own commits by the window of their author: index 0 is the event window,
+/// `i + 1` the member window `windows[i]`.
+fn window_spans(
+    repo: &Repository,
+    windows: &[AuthorWindow],
+    prior_work: &[Oid],
+) -> Result<Vec<Option<WindowSpan>>, git2::Error> {
+    let mut spans: Vec<Option<WindowSpan>> = (0..=windows.len()).map(|_| None).collect();
+    for oid in walk_own_commits(repo, prior_work)? {
+        let commit = repo.find_commit(oid?)?;
+        let author = commit.author();
+        let name = author.name().unwrap_or("Unknown");
+        let email = author.email().unwrap_or("");
+        let (index, author) = windows
+            .iter()
+            .enumerate()
+            .find_map(|(index, window)| {
+                member_of(name, email, &window.usernames).map(|user| (index + 1, user.as_str()))
+            })
+            .unwrap_or((0, name));
+        let time = git_time_to_system_time(commit.time());
+        let span = spans[index].get_or_insert_with(|| WindowSpan {
+            authors: Vec::new(),
+            first: time,
+            last: time,
+        });
+        if !span.authors.iter().any(|a| a == author) {
+            span.authors.push(author.to_string());
+        }
+        span.first = span.first.min(time);
+        span.last = span.last.max(time);
+    }
+    Ok(spans)
+}
+
+/// Checks every author's commits against their own window. The time checks fail with the
+/// earliest first commit and the latest last commit outside their window, citing it.
+fn check_windows(
+    repo: &Repository,
+    constraints: &MetadataConstraints,
+) -> (VerificationResult, VerificationResult, Vec<WindowCheck>) {
+    let spans = match window_spans(repo, &constraints.windows, &constraints.prior_work) {
+        Ok(spans) if spans.iter().any(Option::is_some) => spans,
+        Ok(_) => {
+            let no_commits = || Err(git2::Error::from_str("repository has no commits"));
+            return (
+                verify_time(constraints.first_commit_time.as_ref(), no_commits()),
+                verify_time(constraints.last_commit_time.as_ref(), no_commits()),
+                Vec::new(),
+            );
+        }
+        Err(e) => {
+            let copy = git2::Error::from_str(e.message());
+            return (
+                VerificationResult::Failed(FailureReason::GitError(e)),
+                VerificationResult::Failed(FailureReason::GitError(copy)),
+                Vec::new(),
+            );
+        }
+    };
+
+    let event = (
+        "event".to_string(),
+        constraints.first_commit_time.clone(),
+        constraints.last_commit_time.clone(),
+    );
+    let members = constraints.windows.iter().map(|window| {
+        (
+            window.label.clone(),
+            Some(window.range.clone()),
+            Some(window.range.clone()),
+        )
+    });
+    let mut early: Option<(SystemTime, Option<String>)> = None;
+    let mut late: Option<(SystemTime, Option<String>)> = None;
+    let mut checks = Vec::new();
+    let windows = std::iter::once(event).chain(members).zip(spans);
+    for (index, ((label, first_range, last_range), span)) in windows.enumerate() {
+        let Some(span) = span else { continue };
+        // Failures in the event window keep the plain `TimeNotInRange`.
+        let cited = (index > 0).then(|| label.clone());
+        if let Some(range) = &first_range
+            && !range.contains(&span.first)
+            && early.as_ref().is_none_or(|(t, _)| span.first < *t)
+        {
+            early = Some((span.first, cited.clone()));
+        }
+        if let Some(range) = &last_range
+            && !range.contains(&span.last)
+            && late.as_ref().is_none_or(|(t, _)| span.last > *t)
+        {
+            late = Some((span.last, cited));
+        }
+        if let (Some(first_range), Some(last_range)) = (first_range, last_range) {
+            checks.push(WindowCheck {
+                window: label,
+                start_time: unix_secs(&first_range.start),
+                end_time: unix_secs(&last_range.end),
+                authors: span.authors,
+                first_commit: unix_secs(&span.first),
+                last_commit: unix_secs(&span.last),
+            });
+        }
     }
+
+    let result = |outside: Option<(SystemTime, Option<String>)>| match outside {
+        Some((time, Some(window))) => {
+            VerificationResult::Failed(FailureReason::TimeNotInWindow(time, window))
+        }
+        Some((time, None)) => VerificationResult::Failed(FailureReason::TimeNotInRange(time)),
+        None => VerificationResult::Verified,
+    };
+    (result(early), result(late), checks)
 }
 
 pub fn check_metadata(
     repo: &Repository,
     constraints: MetadataConstraints,
 ) -> MetadataVerificationResult {
-    let first_result = verify_time(
-        constraints.first_commit_time.as_ref(),
-        earliest_commit_time(repo),
-    );
-    let last_result = verify_time(
-        constraints.last_commit_time.as_ref(),
-        latest_commit_time(repo),
-    );
+    let (first_result, last_result, windows) = if constraints.windows.is_empty() {
+        let first_result = verify_time(
+            constraints.first_commit_time.as_ref(),
+            earliest_commit_time(repo, &constraints.prior_work),
+        );
+        let last_result = verify_time(
+            constraints.last_commit_time.as_ref(),
+            latest_commit_time(repo, &constraints.prior_work),
+        );
+        (first_result, last_result, Vec::new())
+    } else {
+        check_windows(repo, &constraints)
+    };
 
+    let mut allowlisted = Vec::new();
     let contributors_result = match constraints.usernames {
-        Some(expected_usernames) => match get_repository_contributors(repo) {
-            Ok(actual_contributors) => {
-                verify_contributors(&actual_contributors, &expected_usernames)
+        Some(expected_usernames) => {
+            match check_contributors(
+                repo,
+                &expected_usernames,
+                &constraints.bots,
+                &constraints.prior_work,
+            ) {
+                Ok(check) if check.unauthorized.is_empty() => {
+                    allowlisted = check.allowlisted;
+                    VerificationResult::Verified
+                }
+                Ok(check) => {
+                    allowlisted = check.allowlisted;
+                    VerificationResult::Failed(FailureReason::AdditionalUnauthorizedUsers(
+                        check.unauthorized,
+                    ))
+                }
+                Err(e) => VerificationResult::Failed(FailureReason::GitError(e)),
             }
-            Err(e) => VerificationResult::Failed(FailureReason::GitError(e)),
-        },
+        }
         None => VerificationResult::Skipped,
     };
 
-    MetadataVerificationResult::new(first_result, last_result, contributors_result)
+    let mut result =
+        MetadataVerificationResult::new(first_result, last_result, contributors_result);
+    result.commits = list_commits(repo).unwrap_or_default();
+    result.windows = windows;
+    result.allowlisted = allowlisted;
+    result
 }
 
 #[cfg(test)]
@@ -321,6 +648,101 @@ mod tests {
         assert!(matches!(res.last_commit_time, VerificationResult::Verified));
         assert!(matches!(res.contributors, VerificationResult::Skipped));
         assert!(res.all_verified_or_skipped());
+        assert_eq!(res.commits.len(), 1);
+        assert_eq!(res.commits[0].author, "tester");
+        assert_eq!(res.commits[0].summary, "initial");
+    }
+
+    #[test]
+    fn test_allowlisted_authors_are_reported_separately() {
+        let (dir, repo, _t) = init_repo_with_one_commit();
+        let dependabot = "49699333+dependabot[bot]@users.noreply.github.com";
+        for (name, email, file) in [
+            ("Tester T.", "7+tester@users.noreply.github.com", "a.txt"),
+            ("dependabot[bot]", dependabot, "b.txt"),
+            ("dependabot[bot]", dependabot, "c.txt"),
+            ("mallory", "mallory@example.com", "d.txt"),
+        ] {
+            fs::write(dir.join(file), "one\ntwo\n").expect("write file");
+            let mut index = repo.index().expect("index");
+            index
+                .add_path(std::path::Path::new(file))
+                .expect("add path");
+            index.write().expect("write index");
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.head().unwrap().peel_to_commit().unwrap();
+            let sig = git2::Signature::now(name, email).expect("sig");
+            repo.commit(Some("HEAD"), &sig, &sig, "change", &tree, &[&parent])
+                .expect("commit");
+        }
+
+        let mut c = MetadataConstraints::new(None, None, Some(vec!["tester".to_string()]));
+        let res = check_metadata(&repo, c.clone());
+        match &res.contributors {
+            VerificationResult::Failed(FailureReason::AdditionalUnauthorizedUsers(users)) => {
+                assert_eq!(users, &vec!["mallory".to_string()])
+            }
+            other => panic!("expected only mallory to be unauthorized, got {:?}", other),
+        }
+        assert_eq!(res.allowlisted.len(), 1);
+        let bot = &res.allowlisted[0];
+        assert_eq!(bot.rule, "*[bot]@users.noreply.github.com");
+        assert_eq!((bot.commits, bot.insertions), (2, 4));
+
+        c.bots = BotAllowlist::new(&["mallory".to_string()], false);
+        let res = check_metadata(&repo, c);
+        match &res.contributors {
+            VerificationResult::Failed(FailureReason::AdditionalUnauthorizedUsers(users)) => {
+                assert_eq!(users, &vec!["dependabot[bot]".to_string()])
+            }
+            other => panic!("expected dependabot to be unauthorized, got {:?}", other),
+        }
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_member_windows_replace_the_event_window() {
+        use crate::git_tools::test_support::{END, START, commit};
+        let dir = unique_temp_dir();
+        let repo = Repository::init_bare(&dir).unwrap();
+        let (start, end) = (START as i64, END as i64);
+        commit(&repo, "alice", start + 60, "a.rs", 1);
+        commit(&repo, "bob", start + 3600, "b.rs", 1);
+        commit(&repo, "alice", end - 60, "a.rs", 2);
+        commit(&repo, "bob", end + 60, "b.rs", 2);
+
+        let at = |secs: i64| SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64);
+        let event = at(start)..at(end);
+        let mut c = MetadataConstraints::new(Some(event.clone()), Some(event), None);
+        c.windows = vec![AuthorWindow {
+            label: "bob (late arrival)".to_string(),
+            usernames: vec!["bob".to_string()],
+            range: at(start + 7200)..at(end + 3600),
+        }];
+        let res = check_metadata(&repo, c);
+        match res.first_commit_time {
+            VerificationResult::Failed(FailureReason::TimeNotInWindow(actual, window)) => {
+                assert_eq!(
+                    (actual, window.as_str()),
+                    (at(start + 3600), "bob (late arrival)")
+                )
+            }
+            other => panic!("expected bob's window to be cited, got {:?}", other),
+        }
+        assert!(matches!(res.last_commit_time, VerificationResult::Verified));
+        let windows: Vec<(&str, &[String])> = res
+            .windows
+            .iter()
+            .map(|w| (w.window.as_str(), w.authors.as_slice()))
+            .collect();
+        assert_eq!(
+            windows,
+            vec![
+                ("event", &["alice".to_string()][..]),
+                ("bob (late arrival)", &["bob".to_string()][..])
+            ]
+        );
+        fs::remove_dir_all(dir).ok();
     }
 
     #[test]
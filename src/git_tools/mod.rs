@@ -1,3 +1,14 @@
+pub mod authors;
+pub mod bots;
+pub mod github_api;
+pub mod github_issue;
 pub mod metadata;
+pub mod new_code;
+pub mod prior_work;
+pub mod provenance;
+pub mod push_times;
 pub mod repository;
+pub mod team;
+#[cfg(test)]
+mod test_support;
 pub mod verification;
@@ -0,0 +1,370 @@
+use crate::git_tools::bots::{member_of, wildcard};
+use crate::git_tools::metadata::AuthorWindow;
+use crate::git_tools::verification::unix_secs;
+use git2::{BlameOptions, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Directories whose content is vendored or produced by a build rather than written.
+const EXCLUDED_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "third_party",
+    "dist",
+    "build",
+    "target",
+    ".venv",
+    "venv",
+    "__pycache__",
+    ".next",
+];
+/// Lock files and minified or mapped output.
+const EXCLUDED_FILES: &[&str] = &[
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Cargo.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "*.min.js",
+    "*.min.css",
+    "*.map",
+];
+/// Markers generators put near the top of their output.
+const GENERATED_MARKERS: &[&str] = &["@generated", "DO NOT EDIT", "auto-generated"];
+/// Larger files are set aside rather than blamed line by line.
+const MAX_BLAME_BYTES: usize = 1024 * 1024;
+
+/// Thresholds on the fraction of surviving lines written during the event, and extra files
+/// to leave out of it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct NewCodePolicy {
+    /// Below this fraction the submission needs review.
+    #[serde(default)]
+    pub review_below: Option<f64>,
+    /// Below this fraction the submission fails.
+    #[serde(default)]
+    pub fail_below: Option<f64>,
+    /// Paths to set aside besides the built-in vendored and generated files; `*` matches
+    /// any characters, including `/`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum NewCodeVerdict {
+    Pass,
+    Review,
+    Fail,
+}
+
+/// Surviving lines of one author or file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LineShare {
+    /// Author (registered username for members) or file path.
+    pub name: String,
+    pub lines: usize,
+    /// Lines last changed by a commit dated inside the window.
+    pub new_lines: usize,
+}
+
+/// A file that git could not blame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BlameError {
+    pub path: String,
+    pub error: String,
+}
+
+/// How much of the submitted code was written during the event, by blame.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NewCodeResult {
+    /// The commit whose tree was blamed: the last one before `end_time` on the first-parent
+    /// history, or HEAD if there is none.
+    pub commit: String,
+    pub lines: usize,
+    pub new_lines: usize,
+    /// Lines last changed by a commit of the declared prior work, left out of `lines`.
+    #[serde(default)]
+    pub prior_lines: usize,
+    /// `new_lines / lines`; absent when no lines were counted.
+    pub fraction: Option<f64>,
+    pub by_author: Vec<LineShare>,
+    pub by_file: Vec<LineShare>,
+    /// Vendored, generated, binary, or oversized files left out of the counts, and files
+    /// that could not be blamed.
+    pub excluded: Vec<String>,
+    /// Why each file in `excluded` that could not be blamed was left out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blame_errors: Vec<BlameError>,
+    pub verdict: NewCodeVerdict,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// `file_patterns` match the file name, `path_patterns` the whole path.
+fn is_excluded(path: &str, file_patterns: &[Regex], path_patterns: &[Regex]) -> bool {
+    let components: Vec<&str> = path.split('/').collect();
+    let (file, dirs) = components.split_last().expect("split yields one item");
+    dirs.iter().any(|dir| EXCLUDED_DIRS.contains(dir))
+        || file_patterns.iter().any(|p| p.is_match(file))
+        || path_patterns.iter().any(|p| p.is_match(path))
+}
+
+fn is_generated(content: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&content[..content.len().min(1024)]);
+    head.lines()
+        .take(5)
+        .any(|line| GENERATED_MARKERS.iter().any(|m| line.contains(m)))
+}
+
+/// The last commit on the first-parent history of HEAD dated before `end`.
+pub fn deadline_commit(repo: &Repository, end: i64) -> Result<Oid, git2::Error> {
+    let head = repo.head()?.peel_to_commit()?;
+    let mut commit = head.clone();
+    loop {
+        if commit.time().seconds() < end {
+            return Ok(commit.id());
+        }
+        match commit.parent(0) {
+            Ok(parent) => commit = parent,
+            Err(_) => return Ok(head.id()),
+        }
+    }
+}
+
+fn add_lines(shares: &mut Vec<LineShare>, name: &str, lines: usize, new_lines: usize) {
+    match shares.iter_mut().find(|share| share.name == name) {
+        Some(share) => {
+            share.lines += lines;
+            share.new_lines += new_lines;
+        }
+        None => shares.push(LineShare {
+            name: name.to_string(),
+            lines,
+            new_lines,
+        }),
+    }
+}
+
+/// `prior_work` and all of its ancestors.
+fn prior_commits(repo: &Repository, prior_work: &[Oid]) -> Result<HashSet<Oid>, git2::Error> {
+    if prior_work.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let mut walk = repo.revwalk()?;
+    for oid in prior_work {
+        walk.push(*oid)?;
+    }
+    walk.collect()
+}
+
+/// Blames every file of the deadline tree and counts the lines introduced by commits
+/// dated inside their author's member window from `windows`, or `start..end` (Unix
+/// seconds) for everyone else. Lines from the declared `prior_work` are
+/// disclosed, so they are counted apart rather than as old code. `None` for a repository
+/// without commits.
+pub fn check_new_code(
+    repo: &Repository,
+    usernames: &[String],
+    windows: &[AuthorWindow],
+    start: u64,
+    end: u64,
+    prior_work: &[Oid],
+    policy: &NewCodePolicy,
+) -> Result<Option<NewCodeResult>, git2::Error> {
+    let event = start as i64..end as i64;
+    if repo.head().is_err() {
+        return Ok(None);
+    }
+    let prior = prior_commits(repo, prior_work)?;
+    // A member window may close after the event does; their work then still counts.
+    let deadline = windows
+        .iter()
+        .map(|window| unix_secs(&window.range.end))
+        .fold(event.end, i64::max);
+    let commit = deadline_commit(repo, deadline)?;
+    let tree = repo.find_commit(commit)?.tree()?;
+    let file_patterns: Vec<Regex> = EXCLUDED_FILES.iter().map(|p| wildcard(p)).collect();
+    let path_patterns: Vec<Regex> = policy.exclude.iter().map(|p| wildcard(p)).collect();
+
+    let mut files = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(ObjectType::Blob) {
+            files.push((
+                format!("{}{}", root, entry.name().unwrap_or("")),
+                entry.id(),
+            ));
+        }
+        TreeWalkResult::Ok
+    })?;
+
+    let mut by_author = Vec::new();
+    let mut by_file = Vec::new();
+    let mut excluded = Vec::new();
+    let mut blame_errors = Vec::new();
+    let mut prior_lines = 0;
+    for (path, blob) in files {
+        let blob = repo.find_blob(blob)?;
+        if is_excluded(&path, &file_patterns, &path_patterns)
+            || blob.is_binary()
+            || blob.size() > MAX_BLAME_BYTES
+            || is_generated(blob.content())
+        {
+            excluded.push(path);
+            continue;
+        }
+        let mut options = BlameOptions::new();
+        options.newest_commit(commit);
+        let blame = match repo.blame_file(Path::new(&path), Some(&mut options)) {
+            Ok(blame) => blame,
+            Err(err) => {
+                blame_errors.push(BlameError {
+                    path: path.clone(),
+                    error: err.message().to_string(),
+                });
+                excluded.push(path);
+                continue;
+            }
+        };
+        for hunk in blame.iter() {
+            if prior.contains(&hunk.final_commit_id()) {
+                prior_lines += hunk.lines_in_hunk();
+                continue;
+            }
+            let signature = hunk.final_signature();
+            let name = signature.name().unwrap_or("Unknown");
+            let email = signature.email().unwrap_or("");
+            let author = member_of(name, email, usernames).map_or(name, |user| user.as_str());
+            let lines = hunk.lines_in_hunk();
+            let window = AuthorWindow::of(windows, name, email, event.clone());
+            let new_lines = if window.contains(&signature.when().seconds()) {
+                lines
+            } else {
+                0
+            };
+            add_lines(&mut by_author, author, lines, new_lines);
+            add_lines(&mut by_file, &path, lines, new_lines);
+        }
+    }
+
+    let lines: usize = by_file.iter().map(|share| share.lines).sum();
+    let new_lines: usize = by_file.iter().map(|share| share.new_lines).sum();
+    let fraction = (lines > 0).then(|| new_lines as f64 / lines as f64);
+    let below = |threshold: Option<f64>| match (fraction, threshold) {
+        (Some(fraction), Some(min)) if fraction < min => Some((fraction, min)),
+        _ => None,
+    };
+    let (verdict, reason) = match (below(policy.fail_below), below(policy.review_below)) {
+        (Some((fraction, min)), _) => (NewCodeVerdict::Fail, Some(("fail", fraction, min))),
+        (None, Some((fraction, min))) => (NewCodeVerdict::Review, Some(("review", fraction, min))),
+        (None, None) => (NewCodeVerdict::Pass, None),
+    };
+    let reason = reason.map(|(threshold, fraction, min)| {
+        format!(
+            "{:.1}% of surviving lines were written during the event, below the {} threshold of {:.1}%",
+            fraction * 100.0,
+            threshold,
+            min * 100.0
+        )
+    });
+    by_author.sort_by(|a, b| b.lines.cmp(&a.lines).then(a.name.cmp(&b.name)));
+    by_file.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(Some(NewCodeResult {
+        commit: commit.to_string(),
+        lines,
+        new_lines,
+        prior_lines,
+        fraction,
+        by_author,
+        by_file,
+        excluded,
+        blame_errors,
+        verdict,
+        reason,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::test_support::{END, START, commit};
+
+    #[test]
+    fn test_old_code_lowers_the_fraction() {
+        let dir = std::env::temp_dir().join(format!("new_code_test_{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+        let start = START as i64;
+        commit(&repo, "alice", start - 86_400, "old.py", 30);
+        commit(&repo, "alice", start + 60, "new.py", 10);
+        commit(&repo, "bob", start + 120, "node_modules/lib/index.js", 500);
+        commit(&repo, "bob", start + 180, "app.min.js", 1);
+        commit(&repo, "bob", start + 240, "generated/api.ts", 1);
+        // Pushed after the deadline, so not part of the blamed tree.
+        let late = commit(&repo, "bob", END as i64 + 60, "late.py", 100);
+
+        let usernames = vec!["alice".to_string(), "bob".to_string()];
+        let policy = NewCodePolicy {
+            review_below: Some(0.5),
+            fail_below: Some(0.2),
+            exclude: vec!["generated/*".to_string()],
+        };
+        let result = check_new_code(&repo, &usernames, &[], START, END, &[], &policy)
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(result.commit, late.to_string());
+        assert_eq!((result.lines, result.new_lines), (40, 10));
+        assert_eq!(result.fraction, Some(0.25));
+        assert_eq!(result.verdict, NewCodeVerdict::Review);
+        assert_eq!(
+            result.excluded,
+            vec![
+                "app.min.js",
+                "generated/api.ts",
+                "node_modules/lib/index.js"
+            ]
+        );
+        assert_eq!(
+            result.by_author,
+            vec![LineShare {
+                name: "alice".to_string(),
+                lines: 40,
+                new_lines: 10
+            }]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_prior_work_lines_are_not_old_code() {
+        let dir = std::env::temp_dir().join(format!("new_code_test_{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+        let base = commit(&repo, "alice", START as i64 - 86_400, "lib.py", 90);
+        commit(&repo, "alice", START as i64 + 60, "app.py", 10);
+
+        let usernames = vec!["alice".to_string()];
+        let policy = NewCodePolicy {
+            fail_below: Some(0.5),
+            ..NewCodePolicy::default()
+        };
+        let undeclared = check_new_code(&repo, &usernames, &[], START, END, &[], &policy)
+            .unwrap()
+            .unwrap();
+        assert_eq!(undeclared.verdict, NewCodeVerdict::Fail);
+
+        let declared = check_new_code(&repo, &usernames, &[], START, END, &[base], &policy)
+            .unwrap()
+            .unwrap();
+        assert_eq!((declared.lines, declared.new_lines), (10, 10));
+        assert_eq!(declared.prior_lines, 90);
+        assert_eq!(declared.verdict, NewCodeVerdict::Pass);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
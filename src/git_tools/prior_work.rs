@@ -0,0 +1,262 @@
+use crate::git_tools::new_code::deadline_commit;
+use git2::{Delta, Direction, Oid, Repository};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Code a team disclosed building on: a commit of the submission's own history, or another
+/// repository. Set either `commit` or `repo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PriorWork {
+    /// Commit (SHA or other revision) of the submission where the event work starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Repository URL or local path the submission builds on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Branch of `repo`; its default branch if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+impl PriorWork {
+    fn label(&self) -> String {
+        match (&self.repo, &self.commit) {
+            (Some(repo), _) => match &self.branch {
+                Some(branch) => format!("{} ({})", repo, branch),
+                None => repo.clone(),
+            },
+            (None, Some(commit)) => format!("commit {}", commit),
+            (None, None) => "empty prior work entry".to_string(),
+        }
+    }
+}
+
+/// A declared prior work entry and the commit it resolved to in the submission repository.
+pub struct ResolvedPriorWork {
+    pub source: String,
+    pub base: Result<Oid, String>,
+}
+
+/// Fetches `branch` of `url` (the default branch if `None`) into `target`.
+fn fetch_prior_repo(
+    repo: &Repository,
+    url: &str,
+    branch: Option<&str>,
+    target: &str,
+) -> Result<Oid, git2::Error> {
+    let mut remote = repo.remote_anonymous(url)?;
+    let branch = match branch {
+        Some(branch) => format!("refs/heads/{}", branch),
+        None => {
+            remote.connect(Direction::Fetch)?;
+            let branch = remote.default_branch()?;
+            let branch = branch.as_str().unwrap_or("HEAD").to_string();
+            remote.disconnect()?;
+            branch
+        }
+    };
+    remote.fetch(&[format!("+{}:{}", branch, target)], None, None)?;
+    repo.refname_to_id(target)
+}
+
+/// Resolves every declared entry, fetching prior repositories into `refs/prior/<index>` of
+/// the submission repository so they can be compared with it.
+pub fn resolve_prior_work(repo: &Repository, declared: &[PriorWork]) -> Vec<ResolvedPriorWork> {
+    declared
+        .iter()
+        .enumerate()
+        .map(|(index, prior)| {
+            let base = match (&prior.repo, &prior.commit) {
+                (Some(url), _) => fetch_prior_repo(
+                    repo,
+                    url,
+                    prior.branch.as_deref(),
+                    &format!("refs/prior/{}", index),
+                ),
+                (None, Some(commit)) => repo
+                    .revparse_single(commit)
+                    .and_then(|object| object.peel_to_commit())
+                    .map(|commit| commit.id()),
+                (None, None) => Err(git2::Error::from_str("set `commit` or `repo`")),
+            };
+            ResolvedPriorWork {
+                source: prior.label(),
+                base: base.map_err(|err| err.message().to_string()),
+            }
+        })
+        .collect()
+}
+
+/// What the submission added on top of one declared prior work.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PriorWorkDelta {
+    pub source: String,
+    /// The prior work commit the submission was compared with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub files_added: usize,
+    pub files_changed: usize,
+    /// Why the entry could not be compared, e.g. an unknown commit or unreachable repo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PriorWorkDelta {
+    /// "built on X, added N lines and M files during the event".
+    pub fn summary(&self) -> String {
+        match &self.error {
+            Some(error) => format!("{} could not be compared: {}", self.source, error),
+            None => format!(
+                "built on {}, added {} lines and {} files during the event",
+                self.source, self.lines_added, self.files_added
+            ),
+        }
+    }
+}
+
+/// The submission measured against its declared prior work.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PriorWorkResult {
+    pub declared: Vec<PriorWorkDelta>,
+    /// Commits dated before the window that no declared prior work contains: reuse that was
+    /// not disclosed.
+    pub undeclared: Vec<String>,
+}
+
+fn delta(repo: &Repository, base: Oid, head: Oid) -> Result<PriorWorkDelta, git2::Error> {
+    let base_tree = repo.find_commit(base)?.tree()?;
+    let head_tree = repo.find_commit(head)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&head_tree), None)?;
+    let stats = diff.stats()?;
+    let files_added = diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Added)
+        .count();
+    Ok(PriorWorkDelta {
+        base: Some(base.to_string()),
+        lines_added: stats.insertions(),
+        lines_removed: stats.deletions(),
+        files_added,
+        files_changed: stats.files_changed() - files_added,
+        ..PriorWorkDelta::default()
+    })
+}
+
+/// Compares the deadline tree with each resolved prior work and lists the commits before
+/// `start` (Unix seconds) that none of them accounts for. An entry that cannot be compared
+/// carries the error instead of stopping the others.
+pub fn measure_prior_work(
+    repo: &Repository,
+    resolved: &[ResolvedPriorWork],
+    start: u64,
+    end: u64,
+) -> Result<PriorWorkResult, git2::Error> {
+    let head = deadline_commit(repo, end as i64)?;
+    let mut declared = Vec::new();
+    for prior in resolved {
+        let mut measured = match &prior.base {
+            Ok(base) => delta(repo, *base, head).unwrap_or_else(|err| PriorWorkDelta {
+                base: Some(base.to_string()),
+                error: Some(err.message().to_string()),
+                ..PriorWorkDelta::default()
+            }),
+            Err(error) => PriorWorkDelta {
+                error: Some(error.clone()),
+                ..PriorWorkDelta::default()
+            },
+        };
+        measured.source = prior.source.clone();
+        declared.push(measured);
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.push_head()?;
+    for base in resolved.iter().filter_map(|prior| prior.base.as_ref().ok()) {
+        walk.hide(*base)?;
+    }
+    let mut undeclared = Vec::new();
+    for oid in walk {
+        let oid = oid?;
+        if repo.find_commit(oid)?.time().seconds() < start as i64 {
+            undeclared.push(oid.to_string());
+        }
+    }
+
+    Ok(PriorWorkResult {
+        declared,
+        undeclared,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::test_support::{END, START, commit};
+
+    #[test]
+    fn test_submission_is_measured_against_prior_repo_and_commit() {
+        let base = std::env::temp_dir().join(format!("prior_work_test_{}", uuid::Uuid::new_v4()));
+        let prior = Repository::init_bare(base.join("prior")).unwrap();
+        let start = START as i64;
+        let old = commit(&prior, "alice", start - 86_400 * 30, "lib.py", 100);
+
+        // The submission continues the prior project, then adds a backdated file.
+        let submission = Repository::init_bare(base.join("submission")).unwrap();
+        let remote = base.join("prior").display().to_string();
+        submission
+            .remote_anonymous(&remote)
+            .unwrap()
+            .fetch(&["+refs/heads/*:refs/heads/*"], None, None)
+            .unwrap();
+        submission
+            .set_head(prior.head().unwrap().name().unwrap())
+            .unwrap();
+        let sneaked = commit(&submission, "alice", start - 3600, "extra.py", 20);
+        commit(&submission, "alice", start + 60, "app.py", 40);
+        commit(&submission, "alice", start + 120, "lib.py", 110);
+
+        let declared = vec![
+            PriorWork {
+                commit: None,
+                repo: Some(remote.clone()),
+                branch: None,
+            },
+            PriorWork {
+                commit: Some(old.to_string()[..7].to_string()),
+                repo: None,
+                branch: None,
+            },
+            PriorWork {
+                commit: Some("0000000".to_string()),
+                repo: None,
+                branch: None,
+            },
+        ];
+        let resolved = resolve_prior_work(&submission, &declared);
+        assert_eq!(resolved[0].base, Ok(old));
+        let result = measure_prior_work(&submission, &resolved, START, END).unwrap();
+
+        let repo_delta = &result.declared[0];
+        assert_eq!(
+            (
+                repo_delta.lines_added,
+                repo_delta.files_added,
+                repo_delta.files_changed
+            ),
+            (70, 2, 1)
+        );
+        assert_eq!(
+            repo_delta.summary(),
+            format!(
+                "built on {}, added 70 lines and 2 files during the event",
+                remote
+            )
+        );
+        assert_eq!(result.declared[1].base, Some(old.to_string()));
+        assert!(result.declared[2].error.is_some());
+        assert_eq!(result.undeclared, vec![sneaked.to_string()]);
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
@@ -0,0 +1,251 @@
+use crate::git_tools::github_api::RepoEvents;
+use crate::git_tools::verification::{FailureReason, VerificationResult};
+use chrono::{DateTime, Utc};
+use octocrab::Octocrab;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+/// What GitHub reports about the submission repository itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct RepoFacts {
+    pub full_name: String,
+    pub fork: bool,
+    /// `owner/name` of the repository this one was forked from.
+    pub parent: Option<String>,
+    /// `owner/name` of the template repository this one was generated from.
+    pub template: Option<String>,
+    /// Unix seconds.
+    pub created_at: Option<i64>,
+    /// Unix seconds of the last push to any branch.
+    pub pushed_at: Option<i64>,
+    pub archived: bool,
+    /// `public`, `private`, or `internal`.
+    pub visibility: Option<String>,
+    /// Unix seconds of the most recent time the repository was made public, if GitHub still
+    /// lists the event (repository events only go back 90 days).
+    pub made_public_at: Option<i64>,
+}
+
+/// Provenance checks on the submission repository's GitHub metadata, reported next to the
+/// commit checks. They are all `Skipped` when the repository could not be looked up.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ProvenanceResult {
+    pub facts: Option<RepoFacts>,
+    pub fork: VerificationResult,
+    pub template: VerificationResult,
+    pub created_at: VerificationResult,
+    pub pushed_at: VerificationResult,
+    pub archived: VerificationResult,
+    pub visibility: VerificationResult,
+}
+
+impl Default for ProvenanceResult {
+    fn default() -> Self {
+        Self {
+            facts: None,
+            fork: VerificationResult::Skipped,
+            template: VerificationResult::Skipped,
+            created_at: VerificationResult::Skipped,
+            pushed_at: VerificationResult::Skipped,
+            archived: VerificationResult::Skipped,
+            visibility: VerificationResult::Skipped,
+        }
+    }
+}
+
+impl ProvenanceResult {
+    /// Every check with a display name, in report order.
+    pub fn checks(&self) -> [(&'static str, &VerificationResult); 6] {
+        [
+            ("Fork", &self.fork),
+            ("Template", &self.template),
+            ("Created on GitHub", &self.created_at),
+            ("Last push", &self.pushed_at),
+            ("Archived", &self.archived),
+            ("Visibility", &self.visibility),
+        ]
+    }
+}
+
+/// `owner` and `name` of a GitHub repository URL, e.g. `https://github.com/a/b.git`.
+pub fn owner_and_name(url: &str) -> Option<(String, String)> {
+    let pattern = Regex::new(r"^https?://(?:www\.)?github\.com/([\w.-]+)/([\w.-]+?)(?:\.git)?/?$")
+        .expect("valid regex");
+    let captures = pattern.captures(url.trim())?;
+    Some((captures[1].to_string(), captures[2].to_string()))
+}
+
+fn unix(time: Option<DateTime<Utc>>) -> Option<i64> {
+    time.map(|time| time.timestamp())
+}
+
+fn system_time(secs: i64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(u64::try_from(secs).unwrap_or_default())
+}
+
+/// Looks up `owner/name`. `events` supplies when it was last made public.
+pub async fn fetch_facts(
+    octocrab: &Octocrab,
+    owner: &str,
+    name: &str,
+    events: &RepoEvents,
+) -> octocrab::Result<RepoFacts> {
+    let repo = octocrab.repos(owner, name).get().await?;
+    let full_name = |repo: &octocrab::models::Repository| {
+        repo.full_name.clone().unwrap_or_else(|| repo.name.clone())
+    };
+    Ok(RepoFacts {
+        full_name: full_name(&repo),
+        fork: repo.fork.unwrap_or(false),
+        parent: repo.parent.as_deref().map(full_name),
+        template: repo.template_repository.as_deref().map(full_name),
+        created_at: unix(repo.created_at),
+        pushed_at: unix(repo.pushed_at),
+        archived: repo.archived.unwrap_or(false),
+        visibility: repo.visibility.clone(),
+        made_public_at: events.made_public_at,
+    })
+}
+
+/// Forks and templates of the event's own boilerplate are expected.
+fn is_boilerplate(full_name: &str, boilerplate: &[String]) -> bool {
+    boilerplate
+        .iter()
+        .filter_map(|entry| owner_and_name(entry))
+        .any(|(owner, name)| format!("{}/{}", owner, name).eq_ignore_ascii_case(full_name))
+}
+
+/// Judges the facts against the event window `start..end` (Unix seconds).
+pub fn check_provenance(
+    facts: RepoFacts,
+    start: u64,
+    end: u64,
+    boilerplate: &[String],
+) -> ProvenanceResult {
+    let (start, end) = (start as i64, end as i64);
+    let fork = match (&facts.parent, facts.fork) {
+        (Some(parent), _) if is_boilerplate(parent, boilerplate) => VerificationResult::Verified,
+        (Some(parent), _) => VerificationResult::Failed(FailureReason::ForkOf(parent.clone())),
+        (None, true) => VerificationResult::Failed(FailureReason::ForkOf("unknown".to_string())),
+        (None, false) => VerificationResult::Verified,
+    };
+    let template = match &facts.template {
+        Some(template) if !is_boilerplate(template, boilerplate) => {
+            VerificationResult::Failed(FailureReason::GeneratedFromTemplate(template.clone()))
+        }
+        _ => VerificationResult::Verified,
+    };
+    let created_at = match facts.created_at {
+        Some(created) if created < start => {
+            VerificationResult::Failed(FailureReason::TimeNotInRange(system_time(created)))
+        }
+        Some(_) => VerificationResult::Verified,
+        None => VerificationResult::Skipped,
+    };
+    let pushed_at = match facts.pushed_at {
+        Some(pushed) if pushed >= end => {
+            VerificationResult::Failed(FailureReason::TimeNotInRange(system_time(pushed)))
+        }
+        Some(_) => VerificationResult::Verified,
+        None => VerificationResult::Skipped,
+    };
+    let archived = if facts.archived {
+        VerificationResult::Failed(FailureReason::Archived)
+    } else {
+        VerificationResult::Verified
+    };
+    let visibility = match (facts.visibility.as_deref(), facts.made_public_at) {
+        (Some(visibility), _) if visibility != "public" => VerificationResult::Failed(
+            FailureReason::VisibilityChanged(visibility.to_string(), None),
+        ),
+        // Private during the event, so its history could not be followed while it was made.
+        (_, Some(made_public)) if made_public >= start => VerificationResult::Failed(
+            FailureReason::VisibilityChanged("public".to_string(), Some(system_time(made_public))),
+        ),
+        _ => VerificationResult::Verified,
+    };
+    ProvenanceResult {
+        facts: Some(facts),
+        fork,
+        template,
+        created_at,
+        pushed_at,
+        archived,
+        visibility,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 1_769_280_135;
+    const END: u64 = 1_769_409_735;
+
+    #[test]
+    fn test_owner_and_name_accepts_common_url_forms() {
+        for url in [
+            "https://github.com/team/sub",
+            "https://github.com/team/sub.git",
+            "https://www.github.com/team/sub/",
+        ] {
+            assert_eq!(
+                owner_and_name(url),
+                Some(("team".to_string(), "sub".to_string()))
+            );
+        }
+        assert_eq!(owner_and_name("git@github.com:team/sub.git"), None);
+    }
+
+    #[test]
+    fn test_clean_repository_passes_every_check() {
+        let facts = RepoFacts {
+            full_name: "team/sub".to_string(),
+            template: Some("org/starter".to_string()),
+            created_at: Some(START as i64 + 60),
+            pushed_at: Some(END as i64 - 60),
+            visibility: Some("public".to_string()),
+            ..RepoFacts::default()
+        };
+        let boilerplate = vec!["https://github.com/org/starter".to_string()];
+        let result = check_provenance(facts, START, END, &boilerplate);
+        for (name, check) in result.checks() {
+            assert!(
+                matches!(check, VerificationResult::Verified),
+                "{}: {:?}",
+                name,
+                check
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_finding_is_reported() {
+        let facts = RepoFacts {
+            full_name: "team/sub".to_string(),
+            fork: true,
+            parent: Some("someone/old-project".to_string()),
+            template: Some("someone/template".to_string()),
+            created_at: Some(START as i64 - 86_400),
+            pushed_at: Some(END as i64 + 60),
+            archived: true,
+            visibility: Some("public".to_string()),
+            made_public_at: Some(END as i64 + 30),
+        };
+        let result = check_provenance(facts, START, END, &[]);
+        for (name, check) in result.checks() {
+            assert!(
+                matches!(check, VerificationResult::Failed(_)),
+                "{}: {:?}",
+                name,
+                check
+            );
+        }
+        assert!(matches!(
+            &result.fork,
+            VerificationResult::Failed(FailureReason::ForkOf(parent)) if parent == "someone/old-project"
+        ));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::git_tools::github_api::Push;
+use crate::ledger::reachable_commits;
+use git2::{Oid, Repository};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Flags single pushes of many commits dated long before the push. Working locally all day
+/// and pushing once is common, so this is off unless a config sets it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BulkPushRule {
+    /// Fewest new commits a push must bring to be considered.
+    pub min_commits: usize,
+    /// How much older than the push its oldest commit must claim to be.
+    pub min_age_hours: u64,
+}
+
+/// A commit date that GitHub's record of pushes contradicts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PushTimeFinding {
+    /// Dated inside the event window, but GitHub first received it after `end_time`.
+    PushedAfterEnd {
+        commit: String,
+        commit_time: i64,
+        branch: String,
+        pushed_at: i64,
+    },
+    /// First pushed once the event had started, but dated before `start_time`.
+    DatedBeforeStart {
+        commit: String,
+        commit_time: i64,
+        branch: String,
+        pushed_at: i64,
+    },
+    /// Many commits arrived in one push, the oldest dated long before it. Only reported
+    /// with a `bulk_push` rule.
+    BulkBackdatedPush {
+        branch: String,
+        head: String,
+        pushed_at: i64,
+        commits: usize,
+        oldest_commit_time: i64,
+    },
+}
+
+impl fmt::Display for PushTimeFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushTimeFinding::PushedAfterEnd {
+                commit,
+                commit_time,
+                branch,
+                pushed_at,
+            } => write!(
+                f,
+                "{} is dated {} but was first pushed to {} at {}, after the event ended",
+                commit.get(..7).unwrap_or(commit),
+                commit_time,
+                branch,
+                pushed_at
+            ),
+            PushTimeFinding::DatedBeforeStart {
+                commit,
+                commit_time,
+                branch,
+                pushed_at,
+            } => write!(
+                f,
+                "{} is dated {}, before the event started, but was first pushed to {} at {}",
+                commit.get(..7).unwrap_or(commit),
+                commit_time,
+                branch,
+                pushed_at
+            ),
+            PushTimeFinding::BulkBackdatedPush {
+                branch,
+                pushed_at,
+                commits,
+                oldest_commit_time,
+                ..
+            } => write!(
+                f,
+                "{} commits dated from {} were pushed to {} at once at {}",
+                commits, oldest_commit_time, branch, pushed_at
+            ),
+        }
+    }
+}
+
+/// Result of comparing commit dates with the pushes GitHub recorded.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PushTimeCheck {
+    /// Number of push events GitHub still lists; older pushes cannot be checked.
+    pub pushes: usize,
+    pub findings: Vec<PushTimeFinding>,
+}
+
+fn commit_oid(repo: &Repository, sha: &str) -> Option<Oid> {
+    Oid::from_str(sha)
+        .ok()
+        .filter(|oid| !oid.is_zero() && repo.find_commit(*oid).is_ok())
+}
+
+/// Attributes every commit of the final history to the first push that brought it to
+/// GitHub and checks its date against that push. `pushes` must be oldest first.
+pub fn check_push_times(
+    repo: &Repository,
+    pushes: &[Push],
+    start: u64,
+    end: u64,
+    bulk_push: Option<&BulkPushRule>,
+) -> Result<PushTimeCheck, git2::Error> {
+    let (start, end) = (start as i64, end as i64);
+    let reachable = reachable_commits(repo)?;
+    let mut pushed: HashSet<Oid> = HashSet::new();
+    let mut findings = Vec::new();
+
+    for push in pushes {
+        let Some(head) = commit_oid(repo, &push.head) else {
+            continue;
+        };
+        let mut walk = repo.revwalk()?;
+        walk.push(head)?;
+        if let Some(before) = commit_oid(repo, &push.before) {
+            walk.hide(before)?;
+        }
+        for oid in &pushed {
+            walk.hide(*oid)?;
+        }
+        let mut introduced = Vec::new();
+        for oid in walk {
+            let oid = oid?;
+            if reachable.contains(&oid) {
+                introduced.push((oid, repo.find_commit(oid)?.committer().when().seconds()));
+            }
+        }
+        pushed.insert(head);
+
+        for (oid, time) in &introduced {
+            let (commit, commit_time) = (oid.to_string(), *time);
+            let (branch, pushed_at) = (push.branch.clone(), push.pushed_at);
+            if pushed_at >= end && (start..end).contains(time) {
+                findings.push(PushTimeFinding::PushedAfterEnd {
+                    commit,
+                    commit_time,
+                    branch,
+                    pushed_at,
+                });
+            } else if pushed_at >= start && *time < start {
+                findings.push(PushTimeFinding::DatedBeforeStart {
+                    commit,
+                    commit_time,
+                    branch,
+                    pushed_at,
+                });
+            }
+        }
+        let oldest = introduced.iter().map(|(_, time)| *time).min();
+        if let Some(rule) = bulk_push
+            && introduced.len() >= rule.min_commits
+            && let Some(oldest) = oldest
+            && oldest < push.pushed_at - (rule.min_age_hours * 3600) as i64
+        {
+            findings.push(PushTimeFinding::BulkBackdatedPush {
+                branch: push.branch.clone(),
+                head: push.head.clone(),
+                pushed_at: push.pushed_at,
+                commits: introduced.len(),
+                oldest_commit_time: oldest,
+            });
+        }
+    }
+
+    Ok(PushTimeCheck {
+        pushes: pushes.len(),
+        findings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::github_api::{fetch_events, mock};
+    use git2::{Signature, Time};
+    use serde_json::json;
+
+    const START: u64 = 1_769_280_000;
+    const END: u64 = 1_769_400_000;
+
+    fn commit(repo: &Repository, parent: Option<Oid>, time: i64) -> Oid {
+        let signature = Signature::new("alice", "alice@example.com", &Time::new(time, 0)).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<_> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        let oid = repo
+            .commit(None, &signature, &signature, "work", &tree, &parents)
+            .unwrap();
+        repo.reference("refs/heads/main", oid, true, "commit")
+            .unwrap();
+        oid
+    }
+
+    fn event(head: Oid, before: Option<Oid>, at: &str) -> serde_json::Value {
+        let before = before.unwrap_or_else(Oid::zero).to_string();
+        json!({"type": "PushEvent", "created_at": at,
+               "payload": {"ref": "refs/heads/main", "before": before, "head": head.to_string()}})
+    }
+
+    #[tokio::test]
+    async fn test_late_and_backdated_pushes_are_flagged() {
+        let dir = std::env::temp_dir().join(format!("push_times_test_{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+
+        // A commit dated before the event, then five spread over the first day, all pushed
+        // together at its end.
+        let backdated = commit(&repo, None, START as i64 - 3600);
+        let mut head = Some(backdated);
+        for hour in 0..5 {
+            head = Some(commit(&repo, head, START as i64 + hour * 3600));
+        }
+        let first_push = head.unwrap();
+        // Dated an hour before the end, but only pushed the next day.
+        let late = commit(&repo, Some(first_push), END as i64 - 3600);
+
+        let events = json!([
+            event(late, Some(first_push), "2026-01-27T12:00:00Z"),
+            event(first_push, None, "2026-01-25T12:00:00Z"),
+        ]);
+        let octocrab = mock::github(json!({}), events).await;
+        let events = fetch_events(&octocrab, "team", "sub").await.unwrap();
+
+        let check = check_push_times(&repo, &events.pushes, START, END, None).unwrap();
+        assert_eq!(check.pushes, 2);
+        assert_eq!(
+            check.findings,
+            vec![
+                PushTimeFinding::DatedBeforeStart {
+                    commit: backdated.to_string(),
+                    commit_time: START as i64 - 3600,
+                    branch: "main".to_string(),
+                    pushed_at: 1_769_342_400,
+                },
+                PushTimeFinding::PushedAfterEnd {
+                    commit: late.to_string(),
+                    commit_time: END as i64 - 3600,
+                    branch: "main".to_string(),
+                    pushed_at: 1_769_515_200,
+                },
+            ]
+        );
+
+        // Pushing a day of work at once is only reported when a rule asks for it.
+        let rule = BulkPushRule {
+            min_commits: 5,
+            min_age_hours: 6,
+        };
+        let check = check_push_times(&repo, &events.pushes, START, END, Some(&rule)).unwrap();
+        assert_eq!(
+            check.findings[1],
+            PushTimeFinding::BulkBackdatedPush {
+                branch: "main".to_string(),
+                head: first_push.to_string(),
+                pushed_at: 1_769_342_400,
+                commits: 6,
+                oldest_commit_time: START as i64 - 3600,
+            }
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
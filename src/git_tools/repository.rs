@@ -1,6 +1,8 @@
 use git2::build::RepoBuilder;
 use git2::{FetchOptions, Repository, Sort};
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, SystemTime};
+use tracing::info;
 
 /// Get the repository creation time, defined as the timestamp of the oldest commit reachable
 /// from HEAD (or from any reference if HEAD is unavailable). Falls back to UNIX_EPOCH
@@ -50,23 +52,26 @@ pub fn get_creation_time(repo: &Repository) -> SystemTime {
     }
 }
 
+/// Returns the full SHA of the commit HEAD points to, if the repository has one.
+pub fn head_commit_id<P: AsRef<std::path::Path>>(path: P) -> Option<String> {
+    let repo = Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GithubRepo {
+    pub url: String,
     pub local_path: String,
 }
 
 impl GithubRepo {
-    pub fn new(link: &str, shallow: bool) -> Result<Self, git2::Error> {
-        let local_path = format!("/tmp/repo_{}", uuid::Uuid::new_v4());
-        Self::new_with_local_path(link, &local_path, shallow)
-    }
-
     pub fn new_with_local_path(
         link: &str,
         local_path: &str,
         shallow: bool,
     ) -> Result<Self, git2::Error> {
-        eprintln!("Creating new Github repo at {}", &local_path);
-        eprintln!("Cloning: {}", link);
+        info!(url = link, path = local_path, shallow, "Cloning repository");
         if shallow {
             let mut opt = FetchOptions::new();
             opt.depth(1);
@@ -77,13 +82,10 @@ impl GithubRepo {
             Repository::clone(&link, &local_path)?;
         }
         Ok(Self {
+            url: link.to_string(),
             local_path: local_path.to_string(),
         })
     }
-
-    pub fn destroy(self) {
-        let _ = std::fs::remove_dir_all(&self.local_path);
-    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,226 @@
+use crate::git_tools::authors::{AuthorRole, AuthorStats};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Event rules on team size and on how much each member must contribute during the window.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TeamRules {
+    /// Most registered members a team may have.
+    #[serde(default)]
+    pub max_team_size: Option<usize>,
+    /// Fewest commits each member must author during the window.
+    #[serde(default)]
+    pub min_commits: Option<usize>,
+    /// Fewest lines each member must add during the window.
+    #[serde(default)]
+    pub min_lines: Option<usize>,
+}
+
+/// What one registered member committed, by author time; a summary of their
+/// [`AuthorStats`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MemberStats {
+    pub username: String,
+    pub commits: usize,
+    pub commits_in_window: usize,
+    /// Lines added and removed by the member's commits inside the window; merge commits
+    /// count none.
+    pub insertions_in_window: usize,
+    pub deletions_in_window: usize,
+    /// Unix seconds.
+    pub first_commit: Option<i64>,
+    pub last_commit: Option<i64>,
+}
+
+impl MemberStats {
+    fn new(username: &str, author: Option<&AuthorStats>) -> Self {
+        let (inside, outside) = match author {
+            Some(author) => (author.in_window.clone(), author.outside_window.clone()),
+            None => Default::default(),
+        };
+        let times = [
+            inside.first_commit,
+            inside.last_commit,
+            outside.first_commit,
+            outside.last_commit,
+        ];
+        Self {
+            username: username.to_string(),
+            commits: inside.commits + outside.commits,
+            commits_in_window: inside.commits,
+            insertions_in_window: inside.insertions,
+            deletions_in_window: inside.deletions,
+            first_commit: times.iter().flatten().min().copied(),
+            last_commit: times.iter().flatten().max().copied(),
+        }
+    }
+}
+
+/// A team that breaks the event's composition rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TeamFinding {
+    TeamTooLarge {
+        size: usize,
+        max: usize,
+    },
+    /// No commit of the member falls inside the window.
+    InactiveMember {
+        member: String,
+    },
+    TooFewCommits {
+        member: String,
+        commits: usize,
+        min: usize,
+    },
+    TooFewLines {
+        member: String,
+        lines: usize,
+        min: usize,
+    },
+}
+
+impl fmt::Display for TeamFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeamFinding::TeamTooLarge { size, max } => {
+                write!(
+                    f,
+                    "the team has {} members, more than the limit of {}",
+                    size, max
+                )
+            }
+            TeamFinding::InactiveMember { member } => {
+                write!(f, "{} has no commits during the event", member)
+            }
+            TeamFinding::TooFewCommits {
+                member,
+                commits,
+                min,
+            } => write!(
+                f,
+                "{} made {} commits during the event, fewer than {}",
+                member, commits, min
+            ),
+            TeamFinding::TooFewLines { member, lines, min } => write!(
+                f,
+                "{} added {} lines during the event, fewer than {}",
+                member, lines, min
+            ),
+        }
+    }
+}
+
+/// Per-member statistics and the rules the team breaks.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TeamCheck {
+    pub members: Vec<MemberStats>,
+    pub findings: Vec<TeamFinding>,
+}
+
+/// Collects the members' statistics from `authors` and checks them against `rules`.
+pub fn check_team(authors: &[AuthorStats], usernames: &[String], rules: &TeamRules) -> TeamCheck {
+    let members: Vec<MemberStats> = usernames
+        .iter()
+        .map(|username| {
+            let author = authors
+                .iter()
+                .find(|a| a.role == AuthorRole::Member && &a.name == username);
+            MemberStats::new(username, author)
+        })
+        .collect();
+
+    let mut findings = Vec::new();
+    if let Some(max) = rules.max_team_size
+        && usernames.len() > max
+    {
+        findings.push(TeamFinding::TeamTooLarge {
+            size: usernames.len(),
+            max,
+        });
+    }
+    for stats in &members {
+        let member = stats.username.clone();
+        if stats.commits_in_window == 0 {
+            findings.push(TeamFinding::InactiveMember { member });
+            continue;
+        }
+        if let Some(min) = rules.min_commits
+            && stats.commits_in_window < min
+        {
+            findings.push(TeamFinding::TooFewCommits {
+                member: member.clone(),
+                commits: stats.commits_in_window,
+                min,
+            });
+        }
+        if let Some(min) = rules.min_lines
+            && stats.insertions_in_window < min
+        {
+            findings.push(TeamFinding::TooFewLines {
+                member,
+                lines: stats.insertions_in_window,
+                min,
+            });
+        }
+    }
+
+    TeamCheck { members, findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::authors::author_stats;
+    use crate::git_tools::bots::BotAllowlist;
+    use crate::git_tools::test_support::{END, START, commit};
+    use git2::Repository;
+
+    #[test]
+    fn test_member_stats_and_findings() {
+        let dir = std::env::temp_dir().join(format!("team_test_{}", uuid::Uuid::new_v4()));
+        let repo = Repository::init_bare(&dir).unwrap();
+        let start = START as i64;
+        commit(&repo, "alice", start - 60, "a.rs", 50);
+        commit(&repo, "alice", start + 60, "b.rs", 30);
+        commit(&repo, "alice", start + 120, "c.rs", 30);
+        commit(&repo, "bob", start + 180, "d.rs", 3);
+        commit(&repo, "carol", start - 120, "e.rs", 10);
+
+        let usernames: Vec<String> = ["alice", "bob", "carol"].map(String::from).to_vec();
+        let authors =
+            author_stats(&repo, &usernames, &BotAllowlist::default(), &[], START, END).unwrap();
+        let rules = TeamRules {
+            max_team_size: Some(2),
+            min_commits: Some(2),
+            min_lines: Some(10),
+        };
+        let check = check_team(&authors, &usernames, &rules);
+
+        let alice = &check.members[0];
+        assert_eq!((alice.commits, alice.commits_in_window), (3, 2));
+        assert_eq!(alice.insertions_in_window, 60);
+        assert_eq!(alice.first_commit, Some(start - 60));
+        assert_eq!(
+            check.findings,
+            vec![
+                TeamFinding::TeamTooLarge { size: 3, max: 2 },
+                TeamFinding::TooFewCommits {
+                    member: "bob".to_string(),
+                    commits: 1,
+                    min: 2
+                },
+                TeamFinding::TooFewLines {
+                    member: "bob".to_string(),
+                    lines: 3,
+                    min: 10
+                },
+                TeamFinding::InactiveMember {
+                    member: "carol".to_string()
+                },
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
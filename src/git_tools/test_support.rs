@@ -0,0 +1,41 @@
+use git2::{Oid, Repository, Signature, Time, Tree};
+
+pub const START: u64 = 1_769_280_000;
+pub const END: u64 = 1_769_400_000;
+
+/// `base` with the blob at `path`, creating subtrees as needed.
+fn insert(repo: &Repository, base: Option<Tree>, path: &str, blob: Oid) -> Oid {
+    let mut builder = repo.treebuilder(base.as_ref()).unwrap();
+    match path.split_once('/') {
+        None => builder.insert(path, blob, 0o100644).unwrap(),
+        Some((dir, rest)) => {
+            let subtree = base
+                .as_ref()
+                .and_then(|tree| tree.get_name(dir))
+                .map(|entry| repo.find_tree(entry.id()).unwrap());
+            let subtree = insert(repo, subtree, rest, blob);
+            builder.insert(dir, subtree, 0o040000).unwrap()
+        }
+    };
+    builder.write().unwrap()
+}
+
+/// Commits a file at `path` with `lines` lines on top of HEAD.
+pub fn commit(repo: &Repository, author: &str, time: i64, path: &str, lines: usize) -> Oid {
+    let email = format!("{}@example.com", author);
+    let signature = Signature::new(author, &email, &Time::new(time, 0)).unwrap();
+    let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+    let blob = repo.blob("line\n".repeat(lines).as_bytes()).unwrap();
+    let tree = insert(repo, parent.as_ref().map(|p| p.tree().unwrap()), path, blob);
+    let tree = repo.find_tree(tree).unwrap();
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "work",
+        &tree,
+        &parents,
+    )
+    .unwrap()
+}
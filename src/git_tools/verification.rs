@@ -1,12 +1,133 @@
-use serde::Serialize;
-use serde::ser::SerializeStruct;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::fmt;
 use std::time::SystemTime;
 
 #[derive(Debug)]
 pub enum FailureReason {
     GitError(git2::Error),
     TimeNotInRange(SystemTime),
+    /// Outside the member window that applies to the commit's author, named by its label.
+    TimeNotInWindow(SystemTime, String),
     AdditionalUnauthorizedUsers(Vec<String>),
+    /// Forked from the given `owner/name`.
+    ForkOf(String),
+    /// Generated from the given `owner/name` template.
+    GeneratedFromTemplate(String),
+    Archived,
+    /// Current visibility, and when the repository was made public if that is known.
+    VisibilityChanged(String, Option<SystemTime>),
+}
+
+pub fn unix_secs(time: &SystemTime) -> i64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn from_unix_secs(secs: i64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(u64::try_from(secs).unwrap_or_default())
+}
+
+/// Serialized form of `FailureReason`, tagged by `error_type`.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "error_type")]
+enum FailureRecord {
+    GitError {
+        error_message: String,
+    },
+    /// `actual_time` is in Unix seconds.
+    TimeNotInRange {
+        actual_time: i64,
+    },
+    /// `actual_time` is in Unix seconds; `window` labels the member window that applied.
+    TimeNotInWindow {
+        actual_time: i64,
+        window: String,
+    },
+    UsernameMismatch {
+        unexpected_contributors: Vec<String>,
+    },
+    ForkOf {
+        parent: String,
+    },
+    GeneratedFromTemplate {
+        template: String,
+    },
+    Archived,
+    /// `made_public_at` is in Unix seconds.
+    VisibilityChanged {
+        visibility: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        made_public_at: Option<i64>,
+    },
+}
+
+impl From<&FailureReason> for FailureRecord {
+    fn from(reason: &FailureReason) -> Self {
+        match reason {
+            FailureReason::GitError(e) => FailureRecord::GitError {
+                error_message: e.message().to_string(),
+            },
+            FailureReason::TimeNotInRange(t) => FailureRecord::TimeNotInRange {
+                actual_time: unix_secs(t),
+            },
+            FailureReason::TimeNotInWindow(t, window) => FailureRecord::TimeNotInWindow {
+                actual_time: unix_secs(t),
+                window: window.clone(),
+            },
+            FailureReason::AdditionalUnauthorizedUsers(unexpected) => {
+                FailureRecord::UsernameMismatch {
+                    unexpected_contributors: unexpected.clone(),
+                }
+            }
+            FailureReason::ForkOf(parent) => FailureRecord::ForkOf {
+                parent: parent.clone(),
+            },
+            FailureReason::GeneratedFromTemplate(template) => {
+                FailureRecord::GeneratedFromTemplate {
+                    template: template.clone(),
+                }
+            }
+            FailureReason::Archived => FailureRecord::Archived,
+            FailureReason::VisibilityChanged(visibility, made_public) => {
+                FailureRecord::VisibilityChanged {
+                    visibility: visibility.clone(),
+                    made_public_at: made_public.as_ref().map(unix_secs),
+                }
+            }
+        }
+    }
+}
+
+impl From<FailureRecord> for FailureReason {
+    fn from(record: FailureRecord) -> Self {
+        match record {
+            FailureRecord::GitError { error_message } => {
+                FailureReason::GitError(git2::Error::from_str(&error_message))
+            }
+            FailureRecord::TimeNotInRange { actual_time } => {
+                FailureReason::TimeNotInRange(from_unix_secs(actual_time))
+            }
+            FailureRecord::TimeNotInWindow {
+                actual_time,
+                window,
+            } => FailureReason::TimeNotInWindow(from_unix_secs(actual_time), window),
+            FailureRecord::UsernameMismatch {
+                unexpected_contributors,
+            } => FailureReason::AdditionalUnauthorizedUsers(unexpected_contributors),
+            FailureRecord::ForkOf { parent } => FailureReason::ForkOf(parent),
+            FailureRecord::GeneratedFromTemplate { template } => {
+                FailureReason::GeneratedFromTemplate(template)
+            }
+            FailureRecord::Archived => FailureReason::Archived,
+            FailureRecord::VisibilityChanged {
+                visibility,
+                made_public_at,
+            } => FailureReason::VisibilityChanged(visibility, made_public_at.map(from_unix_secs)),
+        }
+    }
 }
 
 impl Serialize for FailureReason {
@@ -14,32 +135,64 @@ impl Serialize for FailureReason {
     where
         S: serde::Serializer,
     {
-        match self {
-            FailureReason::GitError(e) => {
-                let mut state = serializer.serialize_struct("FailureReason", 2)?;
-                state.serialize_field("errorType", "GitError")?;
-                state.serialize_field("errorMessage", &e.message())?;
-                state.end()
-            }
+        FailureRecord::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FailureReason {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        FailureRecord::deserialize(deserializer).map(FailureReason::from)
+    }
+}
+
+impl JsonSchema for FailureReason {
+    fn schema_name() -> Cow<'static, str> {
+        "FailureReason".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        FailureRecord::json_schema(generator)
+    }
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureReason::GitError(e) => write!(f, "git error: {}", e.message()),
             FailureReason::TimeNotInRange(t) => {
-                let duration = t.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
-                let secs = duration.as_secs() as i64;
-                let mut state = serializer.serialize_struct("FailureReason", 2)?;
-                state.serialize_field("errorType", "TimeNotInRange")?;
-                state.serialize_field("actualTime", &secs)?;
-                state.end()
+                write!(f, "time {} is outside the allowed window", unix_secs(t))
             }
+            FailureReason::TimeNotInWindow(t, window) => write!(
+                f,
+                "time {} is outside the window of {}",
+                unix_secs(t),
+                window
+            ),
             FailureReason::AdditionalUnauthorizedUsers(unexpected) => {
-                let mut state = serializer.serialize_struct("FailureReason", 2)?;
-                state.serialize_field("errorType", "UsernameMismatch")?;
-                state.serialize_field("unexpectedContributors", unexpected)?;
-                state.end()
+                write!(f, "unexpected contributors: {}", unexpected.join(", "))
+            }
+            FailureReason::ForkOf(parent) => write!(f, "forked from {}", parent),
+            FailureReason::GeneratedFromTemplate(template) => {
+                write!(f, "generated from the template {}", template)
+            }
+            FailureReason::Archived => write!(f, "the repository is archived"),
+            FailureReason::VisibilityChanged(visibility, None) => {
+                write!(f, "the repository is {}", visibility)
             }
+            FailureReason::VisibilityChanged(visibility, Some(t)) => write!(
+                f,
+                "the repository was made {} at {}, after the event started",
+                visibility,
+                unix_secs(t)
+            ),
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum VerificationResult {
     Verified,
     Skipped,
@@ -0,0 +1,471 @@
+use chrono::Utc;
+use git2::{Direction, Oid, Remote, Repository, Sort};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, info_span, warn};
+
+/// Commits may be dated this far after they were first seen, to allow for clock skew
+/// between the team's machines and the watcher.
+const CLOCK_SKEW_SECS: i64 = 300;
+
+/// A branch head seen on the remote by `watch`. The ledger is a JSON-lines file of these,
+/// appended whenever a head changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Observation {
+    pub repo: String,
+    pub branch: String,
+    pub head: String,
+    /// Unix seconds.
+    pub observed_at: i64,
+}
+
+/// Something in the final history that contradicts what `watch` saw during the event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LedgerFinding {
+    /// A head that was pushed during the event is no longer part of the history.
+    MissingHead {
+        branch: String,
+        head: String,
+        observed_at: i64,
+    },
+    /// The branch moved to a commit that does not build on the head seen before it.
+    ForcePush {
+        branch: String,
+        before: String,
+        before_observed_at: i64,
+        after: String,
+        after_observed_at: i64,
+    },
+    /// The commit was already on the remote before the time it claims to have been made.
+    CommitDatedAfterPush {
+        commit: String,
+        commit_time: i64,
+        branch: String,
+        first_seen_at: i64,
+    },
+    /// The ledger could not be compared with the history, e.g. because it is missing or
+    /// malformed, so none of the above could be ruled out.
+    Unchecked { reason: String },
+}
+
+impl fmt::Display for LedgerFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerFinding::MissingHead {
+                branch,
+                head,
+                observed_at,
+            } => write!(
+                f,
+                "{} was the head of {} at {} but is no longer in the history",
+                short(head),
+                branch,
+                observed_at
+            ),
+            LedgerFinding::ForcePush {
+                branch,
+                before,
+                before_observed_at,
+                after,
+                after_observed_at,
+            } => write!(
+                f,
+                "{} was force-pushed from {} (seen at {}) to {} (seen at {})",
+                branch,
+                short(before),
+                before_observed_at,
+                short(after),
+                after_observed_at
+            ),
+            LedgerFinding::CommitDatedAfterPush {
+                commit,
+                commit_time,
+                branch,
+                first_seen_at,
+            } => write!(
+                f,
+                "{} is dated {} but was already on {} at {}",
+                short(commit),
+                commit_time,
+                branch,
+                first_seen_at
+            ),
+            LedgerFinding::Unchecked { reason } => write!(f, "not checked: {}", reason),
+        }
+    }
+}
+
+/// Result of comparing the final history with the ledger.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LedgerCheck {
+    /// Number of recorded heads for this repository.
+    pub observations: usize,
+    pub findings: Vec<LedgerFinding>,
+}
+
+fn short(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+/// Repositories are matched by URL, ignoring case, a trailing slash, and `.git`.
+fn same_repo(a: &str, b: &str) -> bool {
+    let normalize = |url: &str| {
+        url.trim()
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_lowercase()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Reads the ledger at `path`. A missing file is an empty ledger, since `watch` may not
+/// have seen any pushes yet.
+pub fn read_ledger(path: &Path) -> Result<Vec<Observation>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(format!(
+                "The ledger '{}' could not be read: {}",
+                path.display(),
+                err
+            ));
+        }
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                format!(
+                    "Line {} of the ledger '{}' is not a valid observation: {}",
+                    index + 1,
+                    path.display(),
+                    err
+                )
+            })
+        })
+        .collect()
+}
+
+pub fn append_observations(path: &Path, observations: &[Observation]) -> Result<(), String> {
+    let describe = |err: std::io::Error| {
+        format!(
+            "The ledger '{}' could not be written: {}",
+            path.display(),
+            err
+        )
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(describe)?;
+    for observation in observations {
+        let line = serde_json::to_string(observation).map_err(|err| err.to_string())?;
+        writeln!(file, "{}", line).map_err(describe)?;
+    }
+    Ok(())
+}
+
+/// Branch names and head SHAs advertised by the remote at `url`, without fetching.
+pub fn remote_heads(url: &str) -> Result<Vec<(String, String)>, git2::Error> {
+    let mut remote = Remote::create_detached(url)?;
+    remote.connect(Direction::Fetch)?;
+    let heads = remote
+        .list()?
+        .iter()
+        .filter_map(|head| {
+            head.name()
+                .strip_prefix("refs/heads/")
+                .map(|branch| (branch.to_string(), head.oid().to_string()))
+        })
+        .collect();
+    remote.disconnect()?;
+    Ok(heads)
+}
+
+/// Heads of `repo` that differ from the latest ones recorded in `ledger`, stamped with
+/// `now`.
+pub fn poll(repo: &str, ledger: &[Observation], now: i64) -> Result<Vec<Observation>, git2::Error> {
+    let mut latest: HashMap<&str, &str> = HashMap::new();
+    for observation in ledger.iter().filter(|o| same_repo(&o.repo, repo)) {
+        latest.insert(&observation.branch, &observation.head);
+    }
+    Ok(remote_heads(repo)?
+        .into_iter()
+        .filter(|(branch, head)| latest.get(branch.as_str()) != Some(&head.as_str()))
+        .map(|(branch, head)| Observation {
+            repo: repo.to_string(),
+            branch,
+            head,
+            observed_at: now,
+        })
+        .collect())
+}
+
+/// Every commit reachable from a branch, tag, or remote-tracking branch.
+pub fn reachable_commits(repo: &Repository) -> Result<HashSet<Oid>, git2::Error> {
+    let mut walk = repo.revwalk()?;
+    for reference in repo.references()? {
+        if let Ok(commit) = reference?.peel_to_commit() {
+            walk.push(commit.id())?;
+        }
+    }
+    walk.collect()
+}
+
+/// Compares the cloned history of `repo_url` with what `watch` recorded for it. A recorded
+/// head that no branch of the clone reaches was rewritten away.
+pub fn check_history(
+    repo: &Repository,
+    repo_url: &str,
+    ledger: &[Observation],
+) -> Result<LedgerCheck, git2::Error> {
+    let mut observations: Vec<&Observation> = ledger
+        .iter()
+        .filter(|o| same_repo(&o.repo, repo_url))
+        .collect();
+    observations.sort_by_key(|o| o.observed_at);
+
+    // Old heads can still be present as unreachable objects, e.g. in a local clone; they
+    // are used for ancestry checks but do not count as part of the history.
+    let present = |sha: &str| {
+        Oid::from_str(sha)
+            .ok()
+            .filter(|oid| repo.find_commit(*oid).is_ok())
+    };
+    let reachable = reachable_commits(repo)?;
+
+    let mut findings = Vec::new();
+    let mut previous: HashMap<&str, &Observation> = HashMap::new();
+    for observation in &observations {
+        let head = present(&observation.head);
+        if !head.is_some_and(|head| reachable.contains(&head)) {
+            findings.push(LedgerFinding::MissingHead {
+                branch: observation.branch.clone(),
+                head: observation.head.clone(),
+                observed_at: observation.observed_at,
+            });
+        }
+        if let Some(before) = previous.insert(&observation.branch, observation)
+            && let (Some(after), Some(before_oid)) = (head, present(&before.head))
+            && after != before_oid
+            && !repo.graph_descendant_of(after, before_oid)?
+        {
+            findings.push(LedgerFinding::ForcePush {
+                branch: observation.branch.clone(),
+                before: before.head.clone(),
+                before_observed_at: before.observed_at,
+                after: observation.head.clone(),
+                after_observed_at: observation.observed_at,
+            });
+        }
+    }
+
+    // Walk each head in the order it was seen, hiding everything seen earlier, so every
+    // commit is attributed to the first observation that contained it.
+    let mut seen: HashSet<Oid> = HashSet::new();
+    for observation in &observations {
+        let Some(head) = present(&observation.head) else {
+            continue;
+        };
+        let mut walk = repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL)?;
+        walk.push(head)?;
+        for oid in &seen {
+            walk.hide(*oid)?;
+        }
+        for oid in walk {
+            let oid = oid?;
+            let commit_time = repo.find_commit(oid)?.committer().when().seconds();
+            if commit_time > observation.observed_at + CLOCK_SKEW_SECS {
+                findings.push(LedgerFinding::CommitDatedAfterPush {
+                    commit: oid.to_string(),
+                    commit_time,
+                    branch: observation.branch.clone(),
+                    first_seen_at: observation.observed_at,
+                });
+            }
+        }
+        seen.insert(head);
+    }
+
+    Ok(LedgerCheck {
+        observations: observations.len(),
+        findings,
+    })
+}
+
+/// Reads the ledger at `path` and checks the history against it. Unlike `watch`, which
+/// starts from an empty ledger, verification reports a missing or unreadable ledger and a
+/// failed history walk as an [`LedgerFinding::Unchecked`] finding instead of passing.
+pub fn check_ledger(path: &Path, repo: &Repository, repo_url: &str) -> LedgerCheck {
+    let check = if path.exists() {
+        read_ledger(path).and_then(|observations| {
+            check_history(repo, repo_url, &observations).map_err(|err| {
+                format!("The history could not be compared with the ledger: {}", err)
+            })
+        })
+    } else {
+        Err(format!("The ledger '{}' does not exist", path.display()))
+    };
+    check.unwrap_or_else(|reason| LedgerCheck {
+        observations: 0,
+        findings: vec![LedgerFinding::Unchecked { reason }],
+    })
+}
+
+/// A submission repository polled by `watch`.
+#[derive(Debug, Clone)]
+pub struct WatchTarget {
+    pub repo: String,
+    /// End of the event window in Unix seconds.
+    pub end_time: u64,
+    pub ledger: PathBuf,
+}
+
+/// Polls every target each `interval` and appends changed heads to its ledger. Stops after
+/// one round with `once`, or when every event window has been closed for a full interval,
+/// so pushes made in the last minutes are still recorded.
+pub fn watch(targets: &[WatchTarget], interval: Duration, once: bool) -> Result<(), String> {
+    loop {
+        let now = Utc::now().timestamp();
+        for target in targets {
+            let span = info_span!("repo", repo = %target.repo);
+            let _entered = span.enter();
+            let ledger = read_ledger(&target.ledger)?;
+            match poll(&target.repo, &ledger, now) {
+                Ok(observed) => {
+                    for observation in &observed {
+                        info!(
+                            branch = %observation.branch,
+                            head = %observation.head,
+                            "Recorded new head"
+                        );
+                    }
+                    append_observations(&target.ledger, &observed)?;
+                }
+                Err(err) => warn!("Could not list the branches of {}: {}", target.repo, err),
+            }
+        }
+        let closed = targets
+            .iter()
+            .all(|target| now > target.end_time as i64 + interval.as_secs() as i64);
+        if once || closed {
+            return Ok(());
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+    use std::path::PathBuf;
+
+    fn unique_temp_dir() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ledger_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    /// Commits onto `refs/heads/main` of a bare repository, like a push would.
+    fn push(repo: &Repository, parent: Option<Oid>, time: i64, message: &str) -> Oid {
+        let signature = Signature::new("alice", "alice@example.com", &Time::new(time, 0)).unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<_> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        let oid = repo
+            .commit(None, &signature, &signature, message, &tree, &parents)
+            .unwrap();
+        repo.reference("refs/heads/main", oid, true, "push")
+            .unwrap();
+        oid
+    }
+
+    #[test]
+    fn test_watch_and_verify_against_a_local_remote() {
+        let dir = unique_temp_dir();
+        let remote_path = dir.join("remote.git");
+        let remote = Repository::init_bare(&remote_path).unwrap();
+        let url = remote_path.display().to_string();
+        let ledger_path = dir.join("ledger.jsonl");
+
+        let first = push(&remote, None, 1_000, "start");
+        let observed = poll(&url, &[], 1_100).unwrap();
+        assert_eq!(observed.len(), 1);
+        assert_eq!(observed[0].head, first.to_string());
+        append_observations(&ledger_path, &observed).unwrap();
+        let ledger = read_ledger(&ledger_path).unwrap();
+        assert!(poll(&url, &ledger, 1_150).unwrap().is_empty());
+
+        // Dated long after it was seen on the remote.
+        let second = push(&remote, Some(first), 9_000, "feature");
+        append_observations(&ledger_path, &poll(&url, &ledger, 2_000).unwrap()).unwrap();
+        // History rewritten: main now starts from a new root.
+        push(&remote, None, 2_500, "rewritten");
+        let ledger = read_ledger(&ledger_path).unwrap();
+        append_observations(&ledger_path, &poll(&url, &ledger, 3_000).unwrap()).unwrap();
+        let ledger = read_ledger(&ledger_path).unwrap();
+        assert_eq!(ledger.len(), 3);
+
+        let clone = Repository::clone(&url, dir.join("clone")).unwrap();
+        let check = check_history(&clone, &format!("{}/", url), &ledger).unwrap();
+        assert_eq!(check.observations, 3);
+        let missing: Vec<&str> = check
+            .findings
+            .iter()
+            .filter_map(|finding| match finding {
+                LedgerFinding::MissingHead { head, .. } => Some(head.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(missing, vec![first.to_string(), second.to_string()]);
+
+        // Against the remote itself both old heads still exist, so the rewrite shows up as a
+        // force push and the late-dated commit is caught.
+        let check = check_history(&remote, &url, &ledger).unwrap();
+        assert!(check.findings.iter().any(|finding| matches!(
+            finding,
+            LedgerFinding::ForcePush { before, .. } if *before == second.to_string()
+        )));
+        assert!(check.findings.iter().any(|finding| matches!(
+            finding,
+            LedgerFinding::CommitDatedAfterPush { commit, first_seen_at: 2_000, .. }
+                if *commit == second.to_string()
+        )));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_or_malformed_ledger_is_a_finding() {
+        let dir = unique_temp_dir();
+        let repo = Repository::init_bare(dir.join("repo.git")).unwrap();
+        let ledger_path = dir.join("ledger.jsonl");
+
+        let unchecked = |check: LedgerCheck| {
+            matches!(check.findings.as_slice(), [LedgerFinding::Unchecked { .. }])
+        };
+        assert!(unchecked(check_ledger(&ledger_path, &repo, "x")));
+        fs::write(&ledger_path, "not json\n").unwrap();
+        assert!(unchecked(check_ledger(&ledger_path, &repo, "x")));
+        fs::write(&ledger_path, "").unwrap();
+        assert!(check_ledger(&ledger_path, &repo, "x").findings.is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
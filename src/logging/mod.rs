@@ -0,0 +1,100 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, fmt};
+
+/// How much the tool reports while it runs, from the global `-v`/`-q` flags.
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    /// Number of `-v` flags: 1 adds debug detail such as every candidate repository, 2 adds
+    /// trace output.
+    pub verbose: u8,
+    /// Only report warnings and errors.
+    pub quiet: bool,
+    /// File that receives every progress event as one JSON object per line.
+    pub progress: Option<PathBuf>,
+}
+
+impl LogOptions {
+    pub fn level(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::WARN,
+            (false, 0) => LevelFilter::INFO,
+            (false, 1) => LevelFilter::DEBUG,
+            (false, _) => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// Only this crate logs at the chosen level; dependencies such as octocrab and hyper are
+/// limited to warnings so `-vv` stays readable.
+fn targets(level: LevelFilter) -> Targets {
+    Targets::new()
+        .with_target(env!("CARGO_CRATE_NAME"), level)
+        .with_default(LevelFilter::WARN.min(level))
+}
+
+/// Installs the global subscriber: human-readable logs on stderr, filtered by `-v`/`-q`,
+/// and optionally the JSON-lines progress stream. Spans (stage, team, user, repo) appear as
+/// context on every line; in the progress stream they are also reported when they open
+/// and close, so a front end can show which stage is running and how long it took.
+pub fn init(options: &LogOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let human = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .without_time()
+        .with_filter(targets(options.level()));
+
+    let progress = match &options.progress {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| {
+                format!(
+                    "The progress file '{}' could not be created: {}",
+                    path.display(),
+                    err
+                )
+            })?;
+            // The progress stream is independent of `-q`, so a front end always sees it.
+            let level = options.level().max(LevelFilter::INFO);
+            Some(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true)
+                    .with_span_events(FmtSpan::NEW | FmtSpan::CLOSE)
+                    .with_writer(Mutex::new(file))
+                    .with_filter(targets(level)),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(human)
+        .with(progress)
+        .try_init()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_wins_over_verbose() {
+        let options = |verbose, quiet| LogOptions {
+            verbose,
+            quiet,
+            progress: None,
+        };
+        assert_eq!(options(0, false).level(), LevelFilter::INFO);
+        assert_eq!(options(1, false).level(), LevelFilter::DEBUG);
+        assert_eq!(options(3, false).level(), LevelFilter::TRACE);
+        assert_eq!(options(2, true).level(), LevelFilter::WARN);
+    }
+}
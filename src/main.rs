@@ -1,46 +1,208 @@
+mod batch;
+mod config;
 mod git_tools;
+mod ledger;
+mod logging;
+mod outcome;
 mod plag_check;
+mod report;
+mod schema;
+mod server;
+mod webhook;
+mod workspace;
 
+use crate::config::profile::is_url;
+use crate::config::time::EventWindow;
+use crate::config::validate::{ValidationError, validate};
+use crate::config::{ConfigData, load_config, read_config};
+use crate::git_tools::authors::author_stats;
+use crate::git_tools::bots::BotAllowlist;
+use crate::git_tools::github_api::{self, fetch_events};
+use crate::git_tools::github_issue::GithubIssue;
+use crate::git_tools::metadata::MetadataVerificationResult;
+use crate::git_tools::new_code::check_new_code;
+use crate::git_tools::prior_work::{ResolvedPriorWork, measure_prior_work, resolve_prior_work};
+use crate::git_tools::provenance::{check_provenance, fetch_facts, owner_and_name};
+use crate::git_tools::push_times::check_push_times;
+use crate::git_tools::team::check_team;
+use crate::ledger::{LedgerCheck, WatchTarget};
+use crate::logging::LogOptions;
+use crate::outcome::{EXIT_TOOL_ERROR, Outcome, Verdict};
+use crate::plag_check::assets::{AssetCheckResult, find_duplicate_assets, fingerprint_dir};
 use crate::plag_check::copydetect::{CopydetectError, run_copydetect};
 use crate::plag_check::gather_repo::{clone_repos_into_dir, gather_repo_urls_and_sizes_from_user};
-use crate::plag_check::plag_result::{PlagiarismVerificationResult, copy_percentage_from_html};
+use crate::plag_check::plag_result::{
+    PlagiarismVerificationResult, SourceRoot, copy_percentage_from_html, file_matches_from_html,
+    link_sources,
+};
+use crate::plag_check::policy::PlagiarismPolicy;
+use crate::plag_check::preprocess::{
+    BlankedScripts, PreprocessedTree, annotate_matches, preprocess_dir,
+};
 use crate::plag_check::prereq_check::check_prereq;
-use clap::Parser;
-use serde::{Deserialize, Serialize};
+use crate::schema::SCHEMA_VERSION;
+use crate::server::ServeOptions;
+use crate::workspace::cleanup::{DirLock, install_interrupt_handler};
+use crate::workspace::{
+    FETCH_STATE, FetchState, METADATA_STATE, MetadataState, PLAGIARISM_STATE, PlagiarismState,
+    SUBMISSION_STATE, WorkdirOptions, Workspace,
+};
+use clap::{Parser, Subcommand};
+use schemars::JsonSchema;
+use serde::Serialize;
 use std::fs;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use tracing::{Instrument, debug, error, info, info_span, instrument, warn};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ConfigData {
-    repo: String,
-    usernames: Vec<String>,
-    start_time: u64,
-    end_time: u64,
-    #[serde(default = "default_size_threshold")]
-    size_threshold_kb: u32,
-    #[serde(default = "default_display_threshold")]
-    display_threshold: f32,
-}
-
-fn default_size_threshold() -> u32 {
-    100_000 // ~100MB default
-}
-
-fn default_display_threshold() -> f32 {
-    0.33 //Default 33% similarity
-}
-
-#[derive(Debug, Serialize)]
+/// Result of verifying one submission, written to `result.json`.
+#[derive(Debug, Serialize, JsonSchema)]
 struct VerificationOutput {
+    /// Version of this format; see `schema/result.v<version>.schema.json`.
+    #[schemars(extend("const" = SCHEMA_VERSION))]
+    schema_version: u32,
+    outcome: Outcome,
+    window: EventWindow,
+    /// The effective config after merging the event profile, for auditing.
+    config: ConfigData,
     metadata: git_tools::metadata::MetadataVerificationResult,
     plagiarism: PlagiarismVerificationResult,
-    github_issues: Vec<String>,
+    assets: AssetCheckResult,
+    github_issues: Vec<GithubIssue>,
 }
 
 #[derive(Parser, Debug)]
 struct Args {
-    #[arg(short, long, required = true)]
+    /// Config file of a single submission to verify.
+    #[arg(short, long)]
+    path: Option<String>,
+    /// Directory under which per-run temporary workspaces are created.
+    #[arg(long, global = true)]
+    workdir_root: Option<PathBuf>,
+    /// Keep temporary workspaces after the run, e.g. to preserve evidence.
+    #[arg(long, global = true)]
+    keep_workdir: bool,
+    /// Print only the final result as JSON on stdout; progress and logs go to stderr.
+    #[arg(long, global = true)]
+    json: bool,
+    /// Log more detail on stderr; repeat (`-vv`) for trace output.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Only log warnings and errors on stderr.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Also write progress events to this file as JSON lines, e.g. for a live status view.
+    #[arg(long, global = true)]
+    progress: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Arguments shared by the subcommands that run a single stage.
+#[derive(clap::Args, Debug)]
+struct StageArgs {
+    /// Config file of the submission.
+    #[arg(short, long)]
     path: String,
+    /// Workspace directory shared between stages.
+    #[arg(short, long)]
+    workspace: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run every stage: fetch, metadata, plagiarism, and report.
+    Verify {
+        /// Config file of the submission.
+        #[arg(short, long)]
+        path: String,
+        /// Keep clones and stage results in this directory instead of a temporary one.
+        #[arg(short, long)]
+        workspace: Option<String>,
+        /// Directory that receives the results.
+        #[arg(short, long, default_value = "output")]
+        output: String,
+    },
+    /// Clone the submission and its members' other repositories into the workspace.
+    Fetch(StageArgs),
+    /// Check commit times and contributors of the submission.
+    Metadata {
+        #[command(flatten)]
+        stage: StageArgs,
+        /// Also check the repository's provenance and push times on GitHub, which needs
+        /// network access and a token.
+        #[arg(long)]
+        github: bool,
+    },
+    /// Run copydetect and the duplicate asset check against the fetched repositories.
+    Plagiarism(StageArgs),
+    /// Write result.json and the reports from the stored stage results.
+    Report {
+        #[command(flatten)]
+        stage: StageArgs,
+        /// Directory that receives the results.
+        #[arg(short, long, default_value = "output")]
+        output: String,
+    },
+    /// Check a config file for problems without cloning or contacting GitHub.
+    Validate {
+        /// Config file to check.
+        #[arg(short, long)]
+        path: String,
+    },
+    /// Verify every submission listed in a CSV or JSONL manifest.
+    Batch {
+        /// Manifest file; `.csv` is read as CSV, anything else as JSON lines.
+        #[arg(short, long)]
+        manifest: String,
+        /// JSON file with event-level config fields shared by all submissions.
+        #[arg(short, long)]
+        defaults: Option<String>,
+        /// Directory that receives one result directory per team and the batch summary.
+        #[arg(short, long, default_value = "output")]
+        output: String,
+    },
+    /// Poll submission repositories during the event and record every new branch head in
+    /// a push ledger.
+    Watch {
+        /// Config file of a submission to watch; repeat for several.
+        #[arg(short, long)]
+        path: Vec<String>,
+        /// Manifest listing the submissions to watch, as for `batch`.
+        #[arg(short, long)]
+        manifest: Option<String>,
+        /// JSON file with event-level config fields shared by the manifest's submissions.
+        #[arg(short, long)]
+        defaults: Option<String>,
+        /// Ledger to append to; defaults to the `ledger` of each config.
+        #[arg(short, long)]
+        ledger: Option<String>,
+        /// Seconds between polls.
+        #[arg(short, long, default_value_t = 300)]
+        interval: u64,
+        /// Poll once and exit, e.g. when run from cron.
+        #[arg(long)]
+        once: bool,
+    },
+    /// Print the JSON Schema of result.json.
+    Schema,
+    /// Serve an HTTP API that queues verification jobs and returns their results.
+    Serve {
+        /// Address to listen on.
+        #[arg(short, long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+        /// Number of submissions verified at the same time.
+        #[arg(short, long, default_value_t = 2)]
+        concurrency: usize,
+        /// Directory that receives one result directory per job.
+        #[arg(short, long, default_value = "output/jobs")]
+        output: String,
+        /// Directory that `event` profiles in submitted configs are resolved against.
+        #[arg(short, long, default_value = ".")]
+        events_dir: String,
+    },
 }
 
 fn system_time_from_unix_secs(secs: u64) -> std::time::SystemTime {
@@ -49,7 +211,7 @@ fn system_time_from_unix_secs(secs: u64) -> std::time::SystemTime {
 
 fn verify_prerequisites() -> Result<(), Box<dyn std::error::Error>> {
     if !check_prereq() {
-        println!(
+        error!(
             "Missing required tool 'copydetect'. Please install it and ensure it is on your PATH.\n\
              Try one of the following:\n\
                - pipx install copydetect\n\
@@ -61,25 +223,10 @@ fn verify_prerequisites() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn load_config(path: &str) -> Result<ConfigData, Box<dyn std::error::Error>> {
-    let contents = fs::read_to_string(path).map_err(|_e| {
-        format!(
-            "The JSON file provided ('{}') does not exist or could not be read.",
-            path
-        )
-    })?;
-
-    let data: ConfigData = serde_json::from_str(&contents).map_err(|err| {
-        format!(
-            "The JSON file provided ('{}') is not a valid JSON file: {}",
-            path, err
-        )
-    })?;
-
-    Ok(data)
-}
-
-fn build_metadata_constraints(data: &ConfigData) -> git_tools::metadata::MetadataConstraints {
+fn build_metadata_constraints(
+    data: &ConfigData,
+    prior_work: &[ResolvedPriorWork],
+) -> git_tools::metadata::MetadataConstraints {
     git_tools::metadata::MetadataConstraints {
         first_commit_time: Some(
             system_time_from_unix_secs(data.start_time)..system_time_from_unix_secs(data.end_time),
@@ -88,6 +235,21 @@ fn build_metadata_constraints(data: &ConfigData) -> git_tools::metadata::Metadat
             system_time_from_unix_secs(data.start_time)..system_time_from_unix_secs(data.end_time),
         ),
         usernames: Some(data.usernames.clone()),
+        bots: BotAllowlist::new(&data.allowed_bots, data.default_bots),
+        windows: data
+            .member_windows
+            .iter()
+            .map(|window| git_tools::metadata::AuthorWindow {
+                label: window.label(),
+                usernames: window.usernames.clone(),
+                range: system_time_from_unix_secs(window.start_time)
+                    ..system_time_from_unix_secs(window.end_time),
+            })
+            .collect(),
+        prior_work: prior_work
+            .iter()
+            .filter_map(|prior| prior.base.as_ref().ok().copied())
+            .collect(),
     }
 }
 
@@ -115,16 +277,18 @@ async fn collect_user_repos(
     size_threshold_kb: u32,
     start_time: u64,
     clone_repos: bool,
-    github_issues: &mut Vec<String>,
+    github_issues: &mut Vec<GithubIssue>,
 ) -> Vec<git_tools::repository::GithubRepo> {
     let mut all_repos = vec![];
     for user in usernames {
+        let span = info_span!("user", user = %user);
         let urls_with_sizes = match gather_repo_urls_and_sizes_from_user(octocrab, user, start_time)
+            .instrument(span.clone())
             .await
         {
             Ok(urls) => urls,
             Err(err) => {
-                github_issues.push(format!("Failed to list repos for user '{}': {}", user, err));
+                github_issues.push(GithubIssue::listing_failed(user, &err));
                 continue;
             }
         };
@@ -139,6 +303,7 @@ async fn collect_user_repos(
                 size_threshold_kb,
                 github_issues,
             )
+            .instrument(span)
             .await;
             all_repos.extend(repos);
         }
@@ -147,50 +312,157 @@ async fn collect_user_repos(
 }
 
 fn run_plagiarism_check(
-    main_repo_path: &str,
+    main_repo: &git_tools::repository::GithubRepo,
     comparison_repos: &[git_tools::repository::GithubRepo],
+    boilerplate: &[String],
     display_threshold: f32,
+    plagiarism_policy: &PlagiarismPolicy,
     working_dir: &Path,
 ) -> PlagiarismVerificationResult {
-    let comparison_paths: Vec<&str> = comparison_repos
+    let main_repo_path = main_repo.local_path.as_str();
+    let mut test_paths = vec![main_repo_path.to_string()];
+    let mut comparison_paths: Vec<String> = comparison_repos
         .iter()
-        .map(|repo| repo.local_path.as_str())
+        .map(|repo| repo.local_path.clone())
         .collect();
 
-    match run_copydetect(
-        &[main_repo_path],
+    let virtual_dir = working_dir.join("virtual");
+    let test_tree = preprocess_into(Path::new(main_repo_path), &virtual_dir.join("test"));
+    // Extracted scripts would otherwise be compared a second time inside the raw files.
+    let mut blanked: Vec<BlankedScripts> = Vec::new();
+    if let Some(tree) = &test_tree {
+        test_paths.push(tree.root.display().to_string());
+        blanked.extend(blank_scripts(tree));
+    }
+    if !comparison_paths.is_empty() {
+        for (index, repo) in comparison_repos.iter().enumerate() {
+            let target = virtual_dir.join(format!("ref_{}", index));
+            if let Some(tree) = preprocess_into(Path::new(&repo.local_path), &target) {
+                comparison_paths.push(tree.root.display().to_string());
+                blanked.extend(blank_scripts(&tree));
+            }
+        }
+    }
+
+    let test_paths: Vec<&str> = test_paths.iter().map(String::as_str).collect();
+    let comparison_paths: Vec<&str> = comparison_paths.iter().map(String::as_str).collect();
+    let boilerplate: Vec<&str> = boilerplate.iter().map(String::as_str).collect();
+    let report = run_copydetect(
+        &test_paths,
         &comparison_paths,
+        &boilerplate,
         display_threshold,
         working_dir,
-    ) {
+    );
+    drop(blanked);
+    match report {
         Ok(Some(report_path)) => {
             let plag_score = copy_percentage_from_html(&report_path);
-            PlagiarismVerificationResult::new(plag_score, Some(report_path))
+            let mut matches = file_matches_from_html(&report_path);
+            if let Some(tree) = &test_tree {
+                annotate_matches(tree, &mut matches);
+            }
+            let roots: Vec<SourceRoot> = std::iter::once(main_repo)
+                .chain(comparison_repos.iter())
+                .map(|repo| SourceRoot {
+                    local_path: &repo.local_path,
+                    url: &repo.url,
+                    revision: git_tools::repository::head_commit_id(&repo.local_path),
+                })
+                .collect();
+            link_sources(&mut matches, &roots);
+            PlagiarismVerificationResult::new(
+                plag_score,
+                matches,
+                Some(report_path),
+                plagiarism_policy,
+            )
         }
         Ok(None) => {
-            eprintln!("copydetect skipped because no comparison repositories were available.");
-            PlagiarismVerificationResult::manual(None)
+            warn!("copydetect skipped because no comparison repositories were available");
+            PlagiarismVerificationResult::manual(None, plagiarism_policy)
         }
         Err(err) => {
-            eprintln!("copydetect failed: {}", err);
+            warn!("copydetect failed: {}", err);
             let report_path = match err {
                 CopydetectError::MissingReport(path) => Some(path),
                 _ => None,
             };
-            PlagiarismVerificationResult::manual(report_path)
+            PlagiarismVerificationResult::manual(report_path, plagiarism_policy)
+        }
+    }
+}
+
+fn run_asset_check(
+    main_repo_path: &str,
+    comparison_repos: &[git_tools::repository::GithubRepo],
+) -> AssetCheckResult {
+    let submission_assets = match fingerprint_dir(Path::new(main_repo_path)) {
+        Ok(assets) => assets,
+        Err(err) => {
+            warn!("Failed to fingerprint submission assets: {}", err);
+            return AssetCheckResult::default();
+        }
+    };
+    let reference_assets: Vec<(String, Vec<_>)> = comparison_repos
+        .iter()
+        .filter_map(|repo| match fingerprint_dir(Path::new(&repo.local_path)) {
+            Ok(assets) => Some((repo.url.clone(), assets)),
+            Err(err) => {
+                warn!("Failed to fingerprint assets of {}: {}", repo.url, err);
+                None
+            }
+        })
+        .collect();
+    find_duplicate_assets(&submission_assets, &reference_assets)
+}
+
+/// Writes notebook cells and script blocks found under `source` into `target`. Returns
+/// `None` when there was nothing to extract, so callers do not pass empty trees to copydetect.
+fn preprocess_into(source: &Path, target: &Path) -> Option<PreprocessedTree> {
+    match preprocess_dir(source, target) {
+        Ok(tree) if !tree.is_empty() => Some(tree),
+        Ok(_) => None,
+        Err(err) => {
+            warn!(
+                "Failed to extract notebooks and scripts from {}: {}",
+                source.display(),
+                err
+            );
+            None
         }
     }
 }
 
+fn blank_scripts(tree: &PreprocessedTree) -> Option<BlankedScripts> {
+    match tree.blank_scripts() {
+        Ok(blanked) => Some(blanked),
+        Err(err) => {
+            warn!("Failed to blank extracted script blocks: {}", err);
+            None
+        }
+    }
+}
+
+/// Files written into the output directory for a single submission.
+const OUTPUT_FILES: &[&str] = &["result.json", "report.html", "summary.html", "summary.md"];
+
 fn save_results(
     verification_output: &VerificationOutput,
+    config: &ConfigData,
+    output_dir: &Path,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let serialized = serde_json::to_string_pretty(verification_output)?;
-    let output_dir = Path::new("output");
-    if output_dir.exists() {
-        fs::remove_dir_all(output_dir)?;
-    }
     fs::create_dir_all(output_dir)?;
+    let _lock = DirLock::acquire(output_dir)?;
+
+    // Only replace the files this run produces; anything else in the directory is left alone.
+    for name in OUTPUT_FILES {
+        let path = output_dir.join(name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
 
     fs::write(output_dir.join("result.json"), serialized)?;
 
@@ -198,17 +470,19 @@ fn save_results(
         if report_path.exists() {
             fs::copy(report_path, output_dir.join("report.html"))?;
         } else {
-            eprintln!(
+            warn!(
                 "copydetect report was expected at {}, but the file does not exist",
                 report_path.display()
             );
         }
     }
 
+    report::write_reports(verification_output, config, output_dir)?;
+
     Ok(())
 }
 
-fn setup_copydetect_dir(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+fn reset_dir(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     if path.exists() {
         fs::remove_dir_all(path)?;
     }
@@ -216,81 +490,597 @@ fn setup_copydetect_dir(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn cleanup_repos(repos: Vec<git_tools::repository::GithubRepo>, copydetect_path: &Path) {
-    for repo in repos {
-        repo.destroy();
-    }
-    let _ = fs::remove_dir_all(copydetect_path);
-}
-
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    verify_prerequisites()?;
-
-    let args = Args::parse();
-    let data = load_config(&args.path)?;
-
-    println!("Input Data:\n{:?}", &data);
-    println!("\n----------------\n");
-
-    let repo_constraints = build_metadata_constraints(&data);
+/// Clones the submission and the comparison repositories of its members into the workspace.
+#[instrument(name = "stage", skip_all, fields(stage = "fetch", repo = %data.repo))]
+async fn run_fetch_stage(
+    data: &ConfigData,
+    workspace: &Workspace,
+) -> Result<FetchState, Box<dyn std::error::Error>> {
     let mut github_issues = Vec::new();
-    let (github_repo, metadata_result) =
-        match git_tools::repository::GithubRepo::new(&data.repo, false) {
-            Ok(repo) => {
-                let metadata_result =
-                    git_tools::metadata::check_metadata_at_path(&repo.local_path, repo_constraints);
-                (Some(repo), metadata_result)
-            }
-            Err(err) => {
-                github_issues.push(format!(
-                    "Failed to clone main repo '{}': {}",
-                    data.repo, err
-                ));
-                (None, metadata_result_from_clone_error(err))
-            }
-        };
+    let submission = match workspace.submission(&data.repo, true) {
+        Ok(repo) => Some(repo),
+        Err(err) => {
+            github_issues.push(GithubIssue::clone_failed("main repo", &data.repo, &err));
+            None
+        }
+    };
 
-    let copydetect_path = PathBuf::from("/tmp/repo_copydetect");
-    setup_copydetect_dir(&copydetect_path)?;
+    let references_path = workspace.references_dir();
+    reset_dir(&references_path)?;
 
-    let octocrab = octocrab::Octocrab::builder().build()?;
-    let all_repos = collect_user_repos(
+    let octocrab = github_api::client()?;
+    let references = collect_user_repos(
         &octocrab,
         &data.usernames,
         &data.repo,
-        &copydetect_path,
+        &references_path,
         data.size_threshold_kb,
         data.start_time,
-        github_repo.is_some(),
+        submission.is_some(),
         &mut github_issues,
     )
     .await;
 
-    let plagiarism_result = match &github_repo {
-        Some(repo) => run_plagiarism_check(
-            &repo.local_path,
-            &all_repos,
-            data.display_threshold,
-            &copydetect_path,
-        ),
-        None => PlagiarismVerificationResult::manual(None),
+    let boilerplate = fetch_boilerplate(data, workspace, &mut github_issues)?;
+
+    let state = FetchState {
+        references,
+        boilerplate,
+        github_issues,
     };
+    workspace.save_state(FETCH_STATE, &state)?;
+    info!(
+        references = state.references.len(),
+        issues = state.github_issues.len(),
+        "Fetched repositories"
+    );
+    Ok(state)
+}
+
+/// Returns the local directories of the event's boilerplate, cloning repository URLs into
+/// the workspace. Boilerplate that cannot be found is reported instead of failing the run.
+fn fetch_boilerplate(
+    data: &ConfigData,
+    workspace: &Workspace,
+    github_issues: &mut Vec<GithubIssue>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let boilerplate_path = workspace.boilerplate_dir();
+    reset_dir(&boilerplate_path)?;
 
+    let mut dirs = Vec::new();
+    for (index, entry) in data.boilerplate.iter().enumerate() {
+        if !is_url(entry) {
+            if Path::new(entry).is_dir() {
+                dirs.push(entry.clone());
+            } else {
+                github_issues.push(GithubIssue::path_not_found("Boilerplate directory", entry));
+            }
+            continue;
+        }
+        let target = boilerplate_path.join(index.to_string());
+        match git_tools::repository::GithubRepo::new_with_local_path(
+            entry,
+            &target.display().to_string(),
+            true,
+        ) {
+            Ok(repo) => dirs.push(repo.local_path),
+            Err(err) => {
+                github_issues.push(GithubIssue::clone_failed("boilerplate repo", entry, &err))
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+/// Looks up the submission on GitHub: where the repository came from, and when GitHub
+/// received its pushes. A failed lookup is reported as a GitHub issue and leaves those
+/// checks skipped. The push times are only checked against a clone at `local_path`.
+async fn check_github_records(
+    data: &ConfigData,
+    local_path: Option<&str>,
+    metadata: &mut MetadataVerificationResult,
+    github_issues: &mut Vec<GithubIssue>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some((owner, name)) = owner_and_name(&data.repo) else {
+        return Ok(());
+    };
+    let octocrab = github_api::client()?;
+    let lookup = async {
+        let events = fetch_events(&octocrab, &owner, &name).await?;
+        let facts = fetch_facts(&octocrab, &owner, &name, &events).await?;
+        Ok::<_, octocrab::Error>((events, facts))
+    };
+    let (events, facts) = match lookup.await {
+        Ok(found) => found,
+        Err(err) => {
+            github_issues.push(GithubIssue::lookup_failed(&data.repo, &err));
+            return Ok(());
+        }
+    };
+    metadata.provenance =
+        check_provenance(facts, data.start_time, data.end_time, &data.boilerplate);
+    if let Some(path) = local_path {
+        let repo = git2::Repository::open(path)?;
+        let check = check_push_times(
+            &repo,
+            &events.pushes,
+            data.start_time,
+            data.end_time,
+            data.bulk_push.as_ref(),
+        )?;
+        metadata.push_times = Some(check);
+    }
+    Ok(())
+}
+
+/// Compares the clone with the branch heads `watch` recorded, if the config names a ledger.
+fn check_push_timeline(data: &ConfigData, repo: &git2::Repository) -> Option<LedgerCheck> {
+    let path = data.ledger.as_deref()?;
+    Some(ledger::check_ledger(Path::new(path), repo, &data.repo))
+}
+
+/// Repositories to poll in `watch`, each with the ledger it is recorded in.
+fn watch_targets(
+    configs: &[ConfigData],
+    ledger: Option<&str>,
+) -> Result<Vec<WatchTarget>, Box<dyn std::error::Error>> {
+    let mut targets = Vec::new();
+    for data in configs {
+        let Some(path) = ledger.or(data.ledger.as_deref()) else {
+            return Err(format!(
+                "No ledger for {}; pass --ledger or set `ledger` in its config.",
+                data.repo
+            )
+            .into());
+        };
+        targets.push(WatchTarget {
+            repo: data.repo.clone(),
+            end_time: data.end_time,
+            ledger: PathBuf::from(path),
+        });
+    }
+    Ok(targets)
+}
+
+/// Verifies commit times and contributors and, with `github`, the submission's provenance
+/// and push times on GitHub. Only needs the submission clone, which is made on demand if
+/// `fetch` has not run yet.
+#[instrument(name = "stage", skip_all, fields(stage = "metadata", repo = %data.repo))]
+async fn run_metadata_stage(
+    data: &ConfigData,
+    workspace: &Workspace,
+    github: bool,
+) -> Result<MetadataState, Box<dyn std::error::Error>> {
+    let mut github_issues = Vec::new();
+    let (mut metadata, local_path) = match workspace.submission(&data.repo, false) {
+        Ok(repo) => {
+            let git_repo = git2::Repository::open(&repo.local_path)?;
+            let prior_work = resolve_prior_work(&git_repo, &data.prior_work);
+            let constraints = build_metadata_constraints(data, &prior_work);
+            let bots = constraints.bots.clone();
+            let prior_bases = constraints.prior_work.clone();
+            let windows = constraints.windows.clone();
+            let mut metadata = git_tools::metadata::check_metadata(&git_repo, constraints);
+            metadata.push_timeline = check_push_timeline(data, &git_repo);
+            metadata.authors = author_stats(
+                &git_repo,
+                &data.usernames,
+                &bots,
+                &windows,
+                data.start_time,
+                data.end_time,
+            )?;
+            metadata.team = Some(check_team(
+                &metadata.authors,
+                &data.usernames,
+                &data.team_rules,
+            ));
+            metadata.new_code = check_new_code(
+                &git_repo,
+                &data.usernames,
+                &windows,
+                data.start_time,
+                data.end_time,
+                &prior_bases,
+                &data.new_code_policy,
+            )?;
+            if !prior_work.is_empty() {
+                metadata.prior_work = Some(measure_prior_work(
+                    &git_repo,
+                    &prior_work,
+                    data.start_time,
+                    data.end_time,
+                )?);
+            }
+            (metadata, Some(repo.local_path))
+        }
+        Err(err) => {
+            github_issues.push(GithubIssue::clone_failed("main repo", &data.repo, &err));
+            (metadata_result_from_clone_error(err), None)
+        }
+    };
+    if github {
+        check_github_records(
+            data,
+            local_path.as_deref(),
+            &mut metadata,
+            &mut github_issues,
+        )
+        .await?;
+    } else {
+        info!("Skipped the GitHub provenance and push time checks");
+    }
+
+    let state = MetadataState {
+        metadata,
+        github_issues,
+    };
+    workspace.save_state(METADATA_STATE, &state)?;
+    Ok(state)
+}
+
+/// Runs copydetect and the asset check against the repositories gathered by `fetch`.
+#[instrument(name = "stage", skip_all, fields(stage = "plagiarism", repo = %data.repo))]
+fn run_plagiarism_stage(
+    data: &ConfigData,
+    workspace: &Workspace,
+) -> Result<PlagiarismState, Box<dyn std::error::Error>> {
+    let fetch: FetchState = workspace.require_state(FETCH_STATE, "fetch")?;
+    let submission = workspace
+        .load_state::<git_tools::repository::GithubRepo>(SUBMISSION_STATE)?
+        .filter(|repo| repo.url == data.repo && Path::new(&repo.local_path).exists());
+
+    let copydetect_path = workspace.copydetect_dir();
+    reset_dir(&copydetect_path)?;
+
+    let state = match &submission {
+        Some(repo) => PlagiarismState {
+            plagiarism: run_plagiarism_check(
+                repo,
+                &fetch.references,
+                &fetch.boilerplate,
+                data.display_threshold,
+                &data.plagiarism_policy,
+                &copydetect_path,
+            ),
+            assets: run_asset_check(&repo.local_path, &fetch.references),
+        },
+        None => PlagiarismState {
+            plagiarism: PlagiarismVerificationResult::manual(None, &data.plagiarism_policy),
+            assets: AssetCheckResult::default(),
+        },
+    };
+    workspace.save_state(PLAGIARISM_STATE, &state)?;
+    Ok(state)
+}
+
+/// Combines the stored stage results into the final output and writes it to `output_dir`.
+#[instrument(name = "stage", skip_all, fields(stage = "report", repo = %data.repo))]
+fn run_report_stage(
+    data: &ConfigData,
+    workspace: &Workspace,
+    output_dir: &Path,
+) -> Result<VerificationOutput, Box<dyn std::error::Error>> {
+    let fetch: FetchState = workspace.require_state(FETCH_STATE, "fetch")?;
+    let metadata: MetadataState = workspace.require_state(METADATA_STATE, "metadata")?;
+    let mut plagiarism: PlagiarismState =
+        workspace.require_state(PLAGIARISM_STATE, "plagiarism")?;
+
+    let report_path = workspace.copydetect_dir().join("report.html");
+    if report_path.exists() {
+        plagiarism.plagiarism.report_path = Some(report_path);
+    }
+
+    let mut github_issues: Vec<GithubIssue> = Vec::new();
+    for issue in fetch
+        .github_issues
+        .into_iter()
+        .chain(metadata.github_issues)
+    {
+        if !github_issues.contains(&issue) {
+            github_issues.push(issue);
+        }
+    }
+
+    let outcome = outcome::evaluate(
+        &metadata.metadata,
+        &plagiarism.plagiarism,
+        &plagiarism.assets,
+        &github_issues,
+    );
     let verification_output = VerificationOutput {
-        metadata: metadata_result,
-        plagiarism: plagiarism_result,
+        schema_version: SCHEMA_VERSION,
+        outcome,
+        window: data.window(),
+        config: data.clone(),
+        metadata: metadata.metadata,
+        plagiarism: plagiarism.plagiarism,
+        assets: plagiarism.assets,
         github_issues,
     };
+    save_results(&verification_output, data, output_dir)?;
+    info!(
+        verdict = ?verification_output.outcome.verdict,
+        output = %output_dir.display(),
+        "Wrote results"
+    );
+    Ok(verification_output)
+}
+
+/// Runs every stage for one submission in `workspace` and writes its results into
+/// `output_dir`.
+async fn verify_in_workspace(
+    data: &ConfigData,
+    workspace: &Workspace,
+    output_dir: &Path,
+) -> Result<VerificationOutput, Box<dyn std::error::Error>> {
+    run_fetch_stage(data, workspace).await?;
+    run_metadata_stage(data, workspace, true).await?;
+    run_plagiarism_stage(data, workspace)?;
+    let verification_output = run_report_stage(data, workspace, output_dir)?;
+    notify_webhook(data, &verification_output).await;
+    Ok(verification_output)
+}
+
+/// Sends the result to the config's webhook, if any. A failed delivery is only logged: the
+/// results are on disk either way, and the verdict does not depend on it.
+async fn notify_webhook(data: &ConfigData, output: &VerificationOutput) {
+    if let Some(webhook) = &data.webhook
+        && let Err(err) = webhook::deliver(webhook, output).await
+    {
+        error!("Could not deliver results to {}: {}", webhook.url, err);
+    }
+}
 
-    println!("Result Data:\n{:?}", verification_output);
+/// Runs the full pipeline for one submission in a throwaway workspace and writes its results
+/// into `output_dir`.
+async fn verify_submission(
+    data: &ConfigData,
+    workdir: &WorkdirOptions,
+    output_dir: &Path,
+) -> Result<VerificationOutput, Box<dyn std::error::Error>> {
+    let workspace = Workspace::temporary(workdir)?;
+    verify_in_workspace(data, &workspace, output_dir).await
+}
 
-    save_results(&verification_output)?;
+fn log_input(data: &ConfigData) {
+    let window = data.window();
+    info!(
+        repo = %data.repo,
+        members = data.usernames.len(),
+        "Verifying submission from {} to {}",
+        window.start,
+        window.end
+    );
+    debug!(config = ?data, "Loaded config");
+}
 
-    cleanup_repos(all_repos, &copydetect_path);
-    if let Some(repo) = github_repo {
-        repo.destroy();
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    let log_options = LogOptions {
+        verbose: args.verbose,
+        quiet: args.quiet,
+        progress: args.progress.clone(),
+    };
+    if let Err(err) = logging::init(&log_options) {
+        eprintln!("Error: {}", err);
+        return ExitCode::from(EXIT_TOOL_ERROR);
     }
+    match run(args).await {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::from(EXIT_TOOL_ERROR)
+        }
+    }
+}
 
+/// Prints the final result of a command: the JSON value with `--json`, the human summary
+/// otherwise. Everything else the tool prints goes to stderr.
+fn emit<T: Serialize>(
+    json: bool,
+    value: &T,
+    human: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else if !human.is_empty() {
+        println!("{}", human);
+    }
     Ok(())
 }
+
+fn describe_outcome(outcome: &Outcome) -> String {
+    let mut text = format!("Verdict: {:?}", outcome.verdict);
+    for reason in &outcome.reasons {
+        text.push_str(&format!("\n  - {}", reason));
+    }
+    text
+}
+
+async fn run(args: Args) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    if let Err(err) = install_interrupt_handler() {
+        warn!("Failed to install Ctrl-C handler: {}", err);
+    }
+    let json = args.json;
+    let workdir = WorkdirOptions {
+        root: args.workdir_root.unwrap_or_else(std::env::temp_dir),
+        keep: args.keep_workdir,
+    };
+    let command = match (args.command, args.path) {
+        (Some(command), _) => command,
+        (None, Some(path)) => Command::Verify {
+            path,
+            workspace: None,
+            output: "output".to_string(),
+        },
+        (None, None) => return Err("Either --path or a subcommand is required.".into()),
+    };
+
+    let verdict = match command {
+        Command::Verify {
+            path,
+            workspace,
+            output,
+        } => {
+            verify_prerequisites()?;
+            let data = load_config(&path)?;
+            log_input(&data);
+            let verification_output = match workspace {
+                Some(root) => {
+                    let workspace = Workspace::open(root)?;
+                    verify_in_workspace(&data, &workspace, Path::new(&output)).await?
+                }
+                None => verify_submission(&data, &workdir, Path::new(&output)).await?,
+            };
+            let human = format!(
+                "{}\nResults written to {}",
+                describe_outcome(&verification_output.outcome),
+                output
+            );
+            emit(json, &verification_output, &human)?;
+            verification_output.outcome.verdict
+        }
+        Command::Fetch(stage) => {
+            let data = load_config(&stage.path)?;
+            let workspace = Workspace::open(&stage.workspace)?;
+            let state = run_fetch_stage(&data, &workspace).await?;
+            let human = format!(
+                "Fetched {} comparison repositories into {}",
+                state.references.len(),
+                stage.workspace
+            );
+            emit(json, &state, &human)?;
+            Verdict::Verified
+        }
+        Command::Metadata { stage, github } => {
+            let data = load_config(&stage.path)?;
+            let workspace = Workspace::open(&stage.workspace)?;
+            let state = run_metadata_stage(&data, &workspace, github).await?;
+            let outcome = outcome::metadata_outcome(&state.metadata);
+            emit(json, &state.metadata, &describe_outcome(&outcome))?;
+            outcome.verdict
+        }
+        Command::Plagiarism(stage) => {
+            verify_prerequisites()?;
+            let data = load_config(&stage.path)?;
+            let workspace = Workspace::open(&stage.workspace)?;
+            let state = run_plagiarism_stage(&data, &workspace)?;
+            let outcome = outcome::plagiarism_outcome(&state.plagiarism, &state.assets);
+            emit(json, &state.plagiarism, &describe_outcome(&outcome))?;
+            outcome.verdict
+        }
+        Command::Report { stage, output } => {
+            let data = load_config(&stage.path)?;
+            let workspace = Workspace::open(&stage.workspace)?;
+            let verification_output = run_report_stage(&data, &workspace, Path::new(&output))?;
+            notify_webhook(&data, &verification_output).await;
+            let human = format!(
+                "{}\nResults written to {}",
+                describe_outcome(&verification_output.outcome),
+                output
+            );
+            emit(json, &verification_output, &human)?;
+            verification_output.outcome.verdict
+        }
+        Command::Validate { path } => {
+            let data = read_config(&path)?;
+            let issues = validate(&data);
+            let human = if issues.is_empty() {
+                format!(
+                    "{} is valid. Event window: {} to {}",
+                    path,
+                    data.window().start,
+                    data.window().end
+                )
+            } else {
+                ValidationError {
+                    path: path.clone(),
+                    issues: issues.clone(),
+                }
+                .to_string()
+            };
+            emit(json, &issues, &human)?;
+            if !issues.is_empty() {
+                return Ok(ExitCode::from(EXIT_TOOL_ERROR));
+            }
+            Verdict::Verified
+        }
+        Command::Batch {
+            manifest,
+            defaults,
+            output,
+        } => {
+            verify_prerequisites()?;
+            let summary =
+                batch::run_batch(&manifest, defaults.as_deref(), &workdir, Path::new(&output))
+                    .await?;
+            emit(json, &summary, &batch::describe_summary(&summary))?;
+            // A submission that could not be verified at all makes the whole batch a tool error.
+            if summary.iter().any(|entry| entry.error.is_some()) {
+                return Ok(ExitCode::from(EXIT_TOOL_ERROR));
+            }
+            summary
+                .iter()
+                .filter_map(|entry| entry.verdict)
+                .max()
+                .unwrap_or(Verdict::Verified)
+        }
+        Command::Watch {
+            path,
+            manifest,
+            defaults,
+            ledger,
+            interval,
+            once,
+        } => {
+            let mut configs = Vec::new();
+            for path in &path {
+                configs.push(load_config(path)?);
+            }
+            if let Some(manifest) = &manifest {
+                for (team_id, data) in batch::manifest_configs(manifest, defaults.as_deref())? {
+                    match data {
+                        Ok(data) => configs.push(data),
+                        Err(err) => warn!("Not watching team '{}': {}", team_id, err),
+                    }
+                }
+            }
+            if configs.is_empty() {
+                return Err("Nothing to watch; pass --path or --manifest.".into());
+            }
+            let targets = watch_targets(&configs, ledger.as_deref())?;
+            info!(repos = targets.len(), interval, "Watching for pushes");
+            tokio::task::spawn_blocking(move || {
+                ledger::watch(&targets, Duration::from_secs(interval), once)
+            })
+            .await??;
+            Verdict::Verified
+        }
+        Command::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema::result_schema())?
+            );
+            Verdict::Verified
+        }
+        Command::Serve {
+            listen,
+            concurrency,
+            output,
+            events_dir,
+        } => {
+            verify_prerequisites()?;
+            let options = ServeOptions {
+                listen,
+                concurrency,
+                output: PathBuf::from(output),
+                events_dir: PathBuf::from(events_dir),
+            };
+            server::serve(options, workdir).await?;
+            Verdict::Verified
+        }
+    };
+    Ok(verdict.into())
+}
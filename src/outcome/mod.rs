@@ -0,0 +1,291 @@
+use crate::git_tools::github_issue::GithubIssue;
+use crate::git_tools::metadata::MetadataVerificationResult;
+use crate::git_tools::new_code::NewCodeVerdict;
+use crate::git_tools::team::TeamFinding;
+use crate::git_tools::verification::{FailureReason, VerificationResult};
+use crate::plag_check::assets::AssetCheckResult;
+use crate::plag_check::plag_result::PlagiarismVerificationResult;
+use crate::plag_check::policy::PlagiarismVerdict;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::process::ExitCode;
+
+/// Exit status when the tool itself could not complete (bad config, missing copydetect,
+/// unwritable output, ...). Matches what Rust uses when `main` returns an error.
+pub const EXIT_TOOL_ERROR: u8 = 1;
+
+/// Overall result of verifying a submission, ordered from best to worst.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Verdict {
+    Verified,
+    NeedsReview,
+    Failed,
+}
+
+impl Verdict {
+    /// Process exit status for this verdict. 2 is skipped because clap uses it for usage
+    /// errors.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Verdict::Verified => 0,
+            Verdict::NeedsReview => 3,
+            Verdict::Failed => 4,
+        }
+    }
+}
+
+impl From<Verdict> for ExitCode {
+    fn from(verdict: Verdict) -> Self {
+        ExitCode::from(verdict.exit_code())
+    }
+}
+
+/// The verdict together with every finding that kept it from being `Verified`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Outcome {
+    pub verdict: Verdict,
+    pub reasons: Vec<String>,
+}
+
+impl Default for Outcome {
+    fn default() -> Self {
+        Self {
+            verdict: Verdict::Verified,
+            reasons: Vec::new(),
+        }
+    }
+}
+
+impl Outcome {
+    fn record(&mut self, verdict: Verdict, reason: String) {
+        self.verdict = self.verdict.max(verdict);
+        self.reasons.push(reason);
+    }
+
+    fn merge(&mut self, other: Outcome) {
+        self.verdict = self.verdict.max(other.verdict);
+        self.reasons.extend(other.reasons);
+    }
+}
+
+fn record_metadata_check(outcome: &mut Outcome, name: &str, result: &VerificationResult) {
+    match result {
+        VerificationResult::Verified | VerificationResult::Skipped => {}
+        // The check could not run, which says nothing about the submission itself.
+        VerificationResult::Failed(reason @ FailureReason::GitError(_)) => {
+            outcome.record(
+                Verdict::NeedsReview,
+                format!("{} could not be checked: {}", name, reason),
+            );
+        }
+        VerificationResult::Failed(reason) => {
+            outcome.record(Verdict::Failed, format!("{} failed: {}", name, reason));
+        }
+    }
+}
+
+pub fn metadata_outcome(metadata: &MetadataVerificationResult) -> Outcome {
+    let mut outcome = Outcome::default();
+    record_metadata_check(
+        &mut outcome,
+        "first commit time",
+        &metadata.first_commit_time,
+    );
+    record_metadata_check(&mut outcome, "last commit time", &metadata.last_commit_time);
+    record_metadata_check(&mut outcome, "contributors", &metadata.contributors);
+    // Provenance findings have legitimate explanations (a team's own earlier fork, a repo
+    // created the night before), so they only ask for review.
+    for (name, result) in metadata.provenance.checks() {
+        if let VerificationResult::Failed(reason) = result {
+            outcome.record(
+                Verdict::NeedsReview,
+                format!("provenance: {}: {}", name.to_lowercase(), reason),
+            );
+        }
+    }
+    // History rewritten during the event, e.g. an accidental force push, is worth a look
+    // but not proof of anything.
+    if let Some(timeline) = &metadata.push_timeline {
+        for finding in &timeline.findings {
+            outcome.record(Verdict::NeedsReview, format!("push timeline: {}", finding));
+        }
+    }
+    if let Some(team) = &metadata.team {
+        for finding in &team.findings {
+            // Commit counts undercount pair programming, so only the hard size limit fails.
+            let verdict = match finding {
+                TeamFinding::TeamTooLarge { .. } => Verdict::Failed,
+                _ => Verdict::NeedsReview,
+            };
+            outcome.record(verdict, format!("team: {}", finding));
+        }
+    }
+    if let Some(new_code) = &metadata.new_code
+        && let Some(reason) = &new_code.reason
+    {
+        let verdict = match new_code.verdict {
+            NewCodeVerdict::Pass => Verdict::Verified,
+            NewCodeVerdict::Review => Verdict::NeedsReview,
+            NewCodeVerdict::Fail => Verdict::Failed,
+        };
+        outcome.record(verdict, format!("new code: {}", reason));
+    }
+    // Undeclared commits before the window already fail the first commit time check.
+    if let Some(prior_work) = &metadata.prior_work {
+        for delta in prior_work.declared.iter().filter(|d| d.error.is_some()) {
+            outcome.record(
+                Verdict::NeedsReview,
+                format!("prior work: {}", delta.summary()),
+            );
+        }
+    }
+    if let Some(push_times) = &metadata.push_times {
+        for finding in &push_times.findings {
+            outcome.record(Verdict::NeedsReview, format!("push times: {}", finding));
+        }
+    }
+    outcome
+}
+
+pub fn plagiarism_outcome(
+    plagiarism: &PlagiarismVerificationResult,
+    assets: &AssetCheckResult,
+) -> Outcome {
+    let mut outcome = Outcome::default();
+    let verdict = match plagiarism.policy.verdict {
+        PlagiarismVerdict::Pass => Verdict::Verified,
+        PlagiarismVerdict::Review => Verdict::NeedsReview,
+        PlagiarismVerdict::Fail => Verdict::Failed,
+    };
+    for reason in &plagiarism.policy.reasons {
+        outcome.record(verdict, format!("plagiarism: {}", reason));
+    }
+    if verdict != Verdict::Verified && plagiarism.policy.reasons.is_empty() {
+        outcome.record(verdict, "plagiarism policy did not pass".to_string());
+    }
+    if !assets.matches.is_empty() {
+        outcome.record(
+            Verdict::NeedsReview,
+            format!(
+                "{} binary asset(s) duplicate files in other repositories",
+                assets.matches.len()
+            ),
+        );
+    }
+    outcome
+}
+
+/// Combines every check into one verdict. GitHub issues (private or missing repositories,
+/// unknown usernames) mean some comparisons were not made, so they require review.
+pub fn evaluate(
+    metadata: &MetadataVerificationResult,
+    plagiarism: &PlagiarismVerificationResult,
+    assets: &AssetCheckResult,
+    github_issues: &[GithubIssue],
+) -> Outcome {
+    let mut outcome = metadata_outcome(metadata);
+    outcome.merge(plagiarism_outcome(plagiarism, assets));
+    for issue in github_issues {
+        outcome.record(Verdict::NeedsReview, format!("GitHub: {}", issue));
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::team::TeamCheck;
+    use crate::plag_check::policy::PlagiarismPolicy;
+    use std::time::SystemTime;
+
+    fn metadata(contributors: VerificationResult) -> MetadataVerificationResult {
+        MetadataVerificationResult::new(
+            VerificationResult::Verified,
+            VerificationResult::Skipped,
+            contributors,
+        )
+    }
+
+    #[test]
+    fn test_clean_submission_is_verified() {
+        let plagiarism = PlagiarismVerificationResult::new(
+            Some(0.01),
+            Vec::new(),
+            None,
+            &PlagiarismPolicy::default(),
+        );
+        let outcome = evaluate(
+            &metadata(VerificationResult::Verified),
+            &plagiarism,
+            &AssetCheckResult::default(),
+            &[],
+        );
+        assert_eq!(outcome.verdict, Verdict::Verified);
+        assert!(outcome.reasons.is_empty());
+        assert_eq!(outcome.verdict.exit_code(), 0);
+    }
+
+    #[test]
+    fn test_worst_finding_decides_verdict() {
+        let plagiarism = PlagiarismVerificationResult::manual(None, &PlagiarismPolicy::default());
+        let review = evaluate(
+            &metadata(VerificationResult::Failed(FailureReason::GitError(
+                git2::Error::from_str("clone failed"),
+            ))),
+            &plagiarism,
+            &AssetCheckResult::default(),
+            &[],
+        );
+        assert_eq!(review.verdict, Verdict::NeedsReview);
+        assert_eq!(review.reasons.len(), 2);
+
+        let failed = evaluate(
+            &metadata(VerificationResult::Failed(FailureReason::TimeNotInRange(
+                SystemTime::UNIX_EPOCH,
+            ))),
+            &plagiarism,
+            &AssetCheckResult::default(),
+            &[GithubIssue::path_not_found("Directory", "ghost")],
+        );
+        assert_eq!(failed.verdict, Verdict::Failed);
+        assert_eq!(failed.verdict.exit_code(), 4);
+        assert!(failed.reasons.iter().any(|r| r.contains("ghost")));
+    }
+
+    #[test]
+    fn test_provenance_findings_only_need_review() {
+        let mut metadata = metadata(VerificationResult::Verified);
+        metadata.provenance.fork =
+            VerificationResult::Failed(FailureReason::ForkOf("someone/old".to_string()));
+        let outcome = metadata_outcome(&metadata);
+        assert_eq!(outcome.verdict, Verdict::NeedsReview);
+        assert_eq!(
+            outcome.reasons,
+            vec!["provenance: fork: forked from someone/old".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_only_team_size_fails() {
+        let mut metadata = metadata(VerificationResult::Verified);
+        let inactive = TeamFinding::InactiveMember {
+            member: "bob".to_string(),
+        };
+        metadata.team = Some(TeamCheck {
+            members: Vec::new(),
+            findings: vec![inactive.clone()],
+        });
+        assert_eq!(metadata_outcome(&metadata).verdict, Verdict::NeedsReview);
+
+        let too_large = TeamFinding::TeamTooLarge { size: 5, max: 4 };
+        metadata.team.as_mut().unwrap().findings.push(too_large);
+        let outcome = metadata_outcome(&metadata);
+        assert_eq!(outcome.verdict, Verdict::Failed);
+        assert_eq!(
+            outcome.reasons[1],
+            "team: the team has 5 members, more than the limit of 4"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::plag_check::preprocess::collect_files;
+use image::imageops::FilterType;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Files smaller than this are placeholders (`.gitkeep`, empty `__init__` data, ...) and not
+/// worth reporting.
+const MIN_ASSET_SIZE_BYTES: u64 = 256;
+/// Images larger than this are hashed exactly but not decoded for a perceptual hash.
+const MAX_DECODED_IMAGE_BYTES: u64 = 20 * 1024 * 1024;
+/// Maximum number of differing bits between two 64-bit difference hashes for the images to
+/// be reported as near duplicates.
+const NEAR_DUPLICATE_DISTANCE: u32 = 10;
+/// Same heuristic git uses: a NUL byte in the first 8000 bytes marks a file as binary.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+#[derive(Debug, Clone)]
+pub struct AssetFingerprint {
+    pub relative_path: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub perceptual_hash: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum AssetMatchKind {
+    Exact,
+    NearDuplicate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AssetMatch {
+    pub kind: AssetMatchKind,
+    pub submission_path: String,
+    pub submission_size_bytes: u64,
+    pub reference_repo: String,
+    pub reference_path: String,
+    pub reference_size_bytes: u64,
+    /// Differing bits between the perceptual hashes; only set for near-duplicate images.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hamming_distance: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct AssetCheckResult {
+    pub matches: Vec<AssetMatch>,
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn is_binary(path: &Path) -> io::Result<bool> {
+    let mut buffer = vec![0u8; BINARY_SNIFF_BYTES];
+    let mut file = File::open(path)?;
+    let read = file.read(&mut buffer)?;
+    Ok(buffer[..read].contains(&0))
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path)?;
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Difference hash: shrink to 9x8 grayscale and record whether each pixel is brighter than
+/// its right neighbour. Robust to rescaling and re-encoding, unlike a byte hash.
+pub fn difference_hash(path: &Path) -> Option<u64> {
+    let image = image::open(path).ok()?;
+    let small = image.grayscale().resize_exact(9, 8, FilterType::Triangle);
+    let luma = small.to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = luma.get_pixel(x, y)[0];
+            let right = luma.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    Some(hash)
+}
+
+/// Fingerprints every binary file under `root`. Text files are left to copydetect.
+pub fn fingerprint_dir(root: &Path) -> io::Result<Vec<AssetFingerprint>> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files)?;
+
+    let mut fingerprints = Vec::new();
+    for file in files {
+        let Ok(metadata) = file.metadata() else {
+            continue;
+        };
+        let size_bytes = metadata.len();
+        if size_bytes < MIN_ASSET_SIZE_BYTES {
+            continue;
+        }
+        let image = is_image(&file);
+        if !image && !is_binary(&file).unwrap_or(false) {
+            continue;
+        }
+        let Ok(sha256) = sha256_file(&file) else {
+            continue;
+        };
+        let perceptual_hash = if image && size_bytes <= MAX_DECODED_IMAGE_BYTES {
+            difference_hash(&file)
+        } else {
+            None
+        };
+        let relative_path = file
+            .strip_prefix(root)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        fingerprints.push(AssetFingerprint {
+            relative_path,
+            size_bytes,
+            sha256,
+            perceptual_hash,
+        });
+    }
+    Ok(fingerprints)
+}
+
+/// Compares submission assets against each reference repository's assets. Identical content
+/// is reported as `Exact`; images whose perceptual hashes are within
+/// `NEAR_DUPLICATE_DISTANCE` bits are reported as `NearDuplicate`.
+pub fn find_duplicate_assets(
+    submission: &[AssetFingerprint],
+    references: &[(String, Vec<AssetFingerprint>)],
+) -> AssetCheckResult {
+    let mut matches = Vec::new();
+    for asset in submission {
+        for (repo, reference_assets) in references {
+            for reference in reference_assets {
+                let (kind, hamming_distance) = if asset.sha256 == reference.sha256 {
+                    (AssetMatchKind::Exact, None)
+                } else {
+                    match (asset.perceptual_hash, reference.perceptual_hash) {
+                        (Some(a), Some(b)) if (a ^ b).count_ones() <= NEAR_DUPLICATE_DISTANCE => {
+                            (AssetMatchKind::NearDuplicate, Some((a ^ b).count_ones()))
+                        }
+                        _ => continue,
+                    }
+                };
+                matches.push(AssetMatch {
+                    kind,
+                    submission_path: asset.relative_path.clone(),
+                    submission_size_bytes: asset.size_bytes,
+                    reference_repo: repo.clone(),
+                    reference_path: reference.relative_path.clone(),
+                    reference_size_bytes: reference.size_bytes,
+                    hamming_distance,
+                });
+            }
+        }
+    }
+    AssetCheckResult { matches }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Luma};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn unique_temp_dir() -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("assets_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn write_gradient(path: &Path, width: u32, height: u32, noise: u8) {
+        let image = ImageBuffer::from_fn(width, height, |x, y| {
+            let value = ((x * 255) / width) as u8;
+            Luma([value.saturating_add(if (x + y) % 7 == 0 { noise } else { 0 })])
+        });
+        image.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_exact_and_near_duplicates_are_reported() {
+        let submission = unique_temp_dir();
+        let reference = unique_temp_dir();
+
+        let weights: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        fs::write(submission.join("model.bin"), &weights).unwrap();
+        fs::write(reference.join("weights.bin"), &weights).unwrap();
+        fs::write(submission.join("notes.txt"), "plain text ".repeat(100)).unwrap();
+        fs::write(reference.join("notes.txt"), "plain text ".repeat(100)).unwrap();
+
+        write_gradient(&submission.join("sprite.png"), 128, 128, 0);
+        write_gradient(&reference.join("sprite_small.png"), 96, 96, 3);
+
+        let submission_assets = fingerprint_dir(&submission).unwrap();
+        let reference_assets = fingerprint_dir(&reference).unwrap();
+        let result = find_duplicate_assets(
+            &submission_assets,
+            &[("https://github.com/a/b".to_string(), reference_assets)],
+        );
+
+        assert!(
+            result
+                .matches
+                .iter()
+                .all(|m| m.submission_path != "notes.txt")
+        );
+        assert!(
+            result
+                .matches
+                .iter()
+                .any(|m| m.kind == AssetMatchKind::Exact
+                    && m.submission_path == "model.bin"
+                    && m.reference_path == "weights.bin"
+                    && m.submission_size_bytes == 4096)
+        );
+        assert!(
+            result
+                .matches
+                .iter()
+                .any(|m| m.kind == AssetMatchKind::NearDuplicate
+                    && m.submission_path == "sprite.png"
+                    && m.reference_path == "sprite_small.png")
+        );
+
+        let _ = fs::remove_dir_all(&submission);
+        let _ = fs::remove_dir_all(&reference);
+    }
+}
@@ -1,7 +1,8 @@
 use std::fmt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use std::{error, io};
+use tracing::Level;
 
 const DEFAULT_EXTENSIONS: &[&str] = &[
     // General Purpose & Web Backend
@@ -114,6 +115,7 @@ impl error::Error for CopydetectError {
 pub fn run_copydetect(
     test_dirs: &[&str],
     ref_dirs: &[&str],
+    boilerplate_dirs: &[&str],
     display_threshold: f32,
     working_dir: &Path,
 ) -> Result<Option<PathBuf>, CopydetectError> {
@@ -121,17 +123,29 @@ pub fn run_copydetect(
         return Ok(None);
     }
 
-    let status = Command::new("copydetect")
+    let mut command = Command::new("copydetect");
+    command
         .current_dir(working_dir)
         .arg("-t")
         .args(test_dirs)
         .arg("-r")
-        .args(ref_dirs)
+        .args(ref_dirs);
+    if !boilerplate_dirs.is_empty() {
+        command.arg("-b").args(boilerplate_dirs);
+    }
+    let status = command
         .arg("-e")
         .args(DEFAULT_EXTENSIONS)
         .arg("-d")
         .arg(display_threshold.to_string())
         .arg("-a")
+        // Keep stdout free for the final result; copydetect's progress goes to stderr
+        // unless `-q` asked for warnings only.
+        .stdout(if tracing::enabled!(Level::INFO) {
+            Stdio::from(io::stderr())
+        } else {
+            Stdio::null()
+        })
         .status()
         .map_err(CopydetectError::Spawn)?;
 
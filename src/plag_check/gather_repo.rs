@@ -1,3 +1,4 @@
+use crate::git_tools::github_issue::GithubIssue;
 use crate::git_tools::repository::GithubRepo;
 use chrono::{TimeZone, Utc};
 use octocrab::{Octocrab, params};
@@ -5,6 +6,7 @@ use rand::{Rng, rng};
 use std::convert::TryFrom;
 use std::fs;
 use std::path::Path;
+use tracing::{debug, info, info_span, warn};
 
 pub async fn gather_repo_urls_and_sizes_from_user(
     octocrab: &Octocrab,
@@ -26,8 +28,8 @@ pub async fn gather_repo_urls_and_sizes_from_user(
         .and_then(|ts| Utc.timestamp_opt(ts, 0).single());
 
     if cutoff_dt.is_none() {
-        eprintln!(
-            "start_time '{}' could not be converted to a timestamp; skipping time filter.",
+        warn!(
+            "start_time '{}' could not be converted to a timestamp; skipping time filter",
             start_time
         );
     }
@@ -47,11 +49,10 @@ pub async fn gather_repo_urls_and_sizes_from_user(
         }
     }
 
-    println!("\nREPOS:\n");
-    for (x, y) in &res {
-        println!("{}", format!("{}, {}\n", x, y));
+    for (url, size_kb) in &res {
+        debug!(url = %url, size_kb, "Found comparison candidate");
     }
-    println!("\n");
+    info!(count = res.len(), "Listed candidate repositories");
 
     Ok(res)
 }
@@ -77,11 +78,12 @@ pub async fn clone_repos_into_dir(
     repo_urls: Vec<(String, u32)>,
     target_dir: &Path,
     size_threshold_kb: u32,
-    github_issues: &mut Vec<String>,
+    github_issues: &mut Vec<GithubIssue>,
 ) -> Vec<GithubRepo> {
     let mut res = Vec::<GithubRepo>::new();
     let mut total_cumulative_size: u32 = 0;
     for (url, size) in repo_urls {
+        let _span = info_span!("repo", url = %url, size_kb = size).entered();
         if total_cumulative_size + size < size_threshold_kb {
             total_cumulative_size += size;
             let local_path = target_dir.join(random_string(50));
@@ -92,7 +94,8 @@ pub async fn clone_repos_into_dir(
                     }
                 }
                 Err(err) => {
-                    github_issues.push(format!("Failed to clone repo '{}': {}", url, err));
+                    warn!("Failed to clone: {}", err);
+                    github_issues.push(GithubIssue::clone_failed("repo", &url, &err));
                     let _ = fs::remove_dir_all(&local_path);
                 }
             }
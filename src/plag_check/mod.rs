@@ -1,5 +1,8 @@
+pub mod assets;
 pub mod copydetect;
 pub mod gather_repo;
 pub mod plag_result;
+pub mod policy;
+pub mod preprocess;
 pub mod prereq_check;
 pub mod verification;
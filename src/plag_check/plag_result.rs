@@ -1,30 +1,81 @@
+use crate::plag_check::policy::{self, PlagiarismPolicy, PolicyOutcome};
+use crate::plag_check::preprocess::SourceLocation;
 use crate::plag_check::verification::VerificationResult;
 
 use regex::Regex;
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize)]
+/// Highlighted snippets are cut off after this many lines to keep `result.json` readable.
+const MAX_SNIPPET_LINES: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnippetLine {
+    pub number: u32,
+    pub text: String,
+}
+
+/// A single test/reference file pair listed in the copydetect report.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FileMatch {
+    pub test_file: String,
+    pub reference_file: String,
+    pub test_similarity: f64,
+    pub reference_similarity: f64,
+    pub token_overlap: Option<u32>,
+    pub matched_lines: Option<u32>,
+    /// Original notebook cells or script blocks for matches against extracted virtual files.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub origins: Vec<SourceLocation>,
+    /// Highlighted lines of the submission and reference files, for side-by-side display.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test_snippet: Vec<SnippetLine>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reference_snippet: Vec<SnippetLine>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_url: Option<String>,
+    #[serde(skip)]
+    pub highlighted_lines: Vec<u32>,
+}
+
+/// A cloned repository whose local paths appear in copydetect output.
+pub struct SourceRoot<'a> {
+    pub local_path: &'a str,
+    pub url: &'a str,
+    pub revision: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PlagiarismVerificationResult {
     pub result: VerificationResult,
-    #[serde(skip_serializing)]
+    pub policy: PolicyOutcome,
+    pub matches: Vec<FileMatch>,
+    #[serde(skip)]
     pub report_path: Option<PathBuf>,
 }
 
 impl PlagiarismVerificationResult {
-    pub fn new(similarity_percentage: Option<f64>, report_path: Option<PathBuf>) -> Self {
-        let result = match similarity_percentage {
-            Some(percentage) => VerificationResult::Verified(percentage),
-            None => VerificationResult::ManualRequired,
-        };
+    pub fn new(
+        similarity_percentage: Option<f64>,
+        matches: Vec<FileMatch>,
+        report_path: Option<PathBuf>,
+        plagiarism_policy: &PlagiarismPolicy,
+    ) -> Self {
+        let policy = policy::evaluate(plagiarism_policy, similarity_percentage, &matches);
+        let result = VerificationResult::new(similarity_percentage, policy.verdict);
         Self {
             result,
+            policy,
+            matches,
             report_path,
         }
     }
 
-    pub fn manual(report_path: Option<PathBuf>) -> Self {
-        Self::new(None, report_path)
+    pub fn manual(report_path: Option<PathBuf>, plagiarism_policy: &PlagiarismPolicy) -> Self {
+        Self::new(None, Vec::new(), report_path, plagiarism_policy)
     }
 }
 
@@ -38,3 +89,239 @@ pub fn copy_percentage_from_html(html_path: &Path) -> Option<f64> {
     let percent = percent_str.parse::<f64>().ok()?;
     Some(percent / 100.0)
 }
+
+/// Extracts the per-file matches from a copydetect report. Sections that cannot be parsed
+/// are skipped, so an unexpected report layout yields an empty list rather than an error.
+pub fn file_matches_from_html(html_path: &Path) -> Vec<FileMatch> {
+    match std::fs::read_to_string(html_path) {
+        Ok(contents) => file_matches_from_str(&contents),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn file_matches_from_str(contents: &str) -> Vec<FileMatch> {
+    let header = Regex::new(
+        r#"(?s)Test file:</b>\s*(.+?)\s*\(([\d.]+)%\).*?Reference file:</b>\s*(.+?)\s*\(([\d.]+)%\)"#,
+    )
+    .expect("valid regex");
+    let overlap = Regex::new(r#"Token overlap:</b>\s*(\d+)"#).expect("valid regex");
+    let pre_block = Regex::new(r#"(?s)<pre[^>]*>(.*?)</pre>"#).expect("valid regex");
+
+    let starts: Vec<usize> = header.find_iter(contents).map(|m| m.start()).collect();
+    let mut matches = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(contents.len());
+        let section = &contents[*start..end];
+        let Some(captures) = header.captures(section) else {
+            continue;
+        };
+        let (Ok(test_pct), Ok(ref_pct)) = (captures[2].parse::<f64>(), captures[4].parse::<f64>())
+        else {
+            continue;
+        };
+        let token_overlap = overlap
+            .captures(section)
+            .and_then(|c| c[1].parse::<u32>().ok());
+        let mut blocks = pre_block.captures_iter(section);
+        let test_block = blocks.next().map(|c| highlighted_lines(&c[1]));
+        let reference_block = blocks.next().map(|c| highlighted_lines(&c[1]));
+        let matched_lines = test_block.as_ref().map(|lines| lines.len() as u32);
+        let highlighted_numbers = test_block
+            .as_ref()
+            .map(|lines| lines.iter().map(|l| l.number).collect())
+            .unwrap_or_default();
+        matches.push(FileMatch {
+            test_file: captures[1].trim().to_string(),
+            reference_file: captures[3].trim().to_string(),
+            test_similarity: test_pct / 100.0,
+            reference_similarity: ref_pct / 100.0,
+            token_overlap,
+            matched_lines,
+            origins: Vec::new(),
+            test_snippet: truncate_snippet(test_block.unwrap_or_default()),
+            reference_snippet: truncate_snippet(reference_block.unwrap_or_default()),
+            test_url: None,
+            reference_url: None,
+            highlighted_lines: highlighted_numbers,
+        });
+    }
+    matches
+}
+
+fn truncate_snippet(mut lines: Vec<SnippetLine>) -> Vec<SnippetLine> {
+    lines.truncate(MAX_SNIPPET_LINES);
+    lines
+}
+
+/// Returns the lines inside a highlighted `<pre>` block that are covered by a highlight span,
+/// including lines in the middle of spans that cross line breaks. Line numbers are 1-based
+/// and the text has its markup removed.
+fn highlighted_lines(block: &str) -> Vec<SnippetLine> {
+    let tag = Regex::new(r#"<[^>]*>"#).expect("valid regex");
+    let mut open_spans: i64 = 0;
+    let mut lines = Vec::new();
+    for (index, line) in block.lines().enumerate() {
+        if open_spans > 0 || line.contains("highlight") {
+            lines.push(SnippetLine {
+                number: index as u32 + 1,
+                text: unescape_html(&tag.replace_all(line, "")),
+            });
+        }
+        open_spans += line.matches("<span").count() as i64;
+        open_spans -= line.matches("</span>").count() as i64;
+        open_spans = open_spans.max(0);
+    }
+    lines
+}
+
+fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Builds a GitHub blob link for a local file under one of `roots`, anchored to the given
+/// line range when known. Notebooks are rendered by cell, so their links carry no anchor.
+fn source_url(roots: &[SourceRoot], local_file: &str, lines: Option<(u32, u32)>) -> Option<String> {
+    let root = roots
+        .iter()
+        .find(|root| Path::new(local_file).starts_with(root.local_path))?;
+    let relative = Path::new(local_file).strip_prefix(root.local_path).ok()?;
+    let base = root.url.trim_end_matches('/').trim_end_matches(".git");
+    let revision = root.revision.as_deref().unwrap_or("HEAD");
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    let anchor = match lines.filter(|_| !relative.ends_with(".ipynb")) {
+        Some((start, end)) if start == end => format!("#L{}", start),
+        Some((start, end)) => format!("#L{}-L{}", start, end),
+        None => String::new(),
+    };
+    Some(format!("{}/blob/{}/{}{}", base, revision, relative, anchor))
+}
+
+fn line_span(lines: &[SnippetLine]) -> Option<(u32, u32)> {
+    Some((lines.first()?.number, lines.last()?.number))
+}
+
+/// Fills in `test_url` and `reference_url` for matches whose files live under a known clone.
+/// Matches against extracted notebook or script files link to the original file instead.
+pub fn link_sources(matches: &mut [FileMatch], roots: &[SourceRoot]) {
+    for file_match in matches.iter_mut() {
+        file_match.test_url = match file_match.origins.first() {
+            Some(origin) => source_url(
+                roots,
+                &origin.file,
+                Some((origin.start_line, origin.end_line)),
+            ),
+            None => source_url(
+                roots,
+                &file_match.test_file,
+                line_span(&file_match.test_snippet),
+            ),
+        };
+        file_match.reference_url = source_url(
+            roots,
+            &file_match.reference_file,
+            line_span(&file_match.reference_snippet),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_matches_from_report_sections() {
+        let html = r#"
+            <b>Number above display threshold:</b> 1 (50.00%)<br><br>
+            <b>Test file:</b> /tmp/sub/a.py (82.50%)<br>
+            <b>Reference file:</b> /tmp/ref/a.py (61.00%)<br>
+            <b>Token overlap:</b> 120<br>
+            <pre>x = 1
+<span class='highlight-red'>def f():</span>
+<span class='highlight-red'>    return 2</span></pre>
+            <pre>ignored</pre>
+            <b>Test file:</b> /tmp/sub/b.py (40.00%)<br>
+            <b>Reference file:</b> /tmp/ref/b.py (10.00%)<br>
+        "#;
+        let matches = file_matches_from_str(html);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].test_file, "/tmp/sub/a.py");
+        assert_eq!(matches[0].reference_file, "/tmp/ref/a.py");
+        assert!((matches[0].test_similarity - 0.825).abs() < 1e-9);
+        assert_eq!(matches[0].token_overlap, Some(120));
+        assert_eq!(matches[0].matched_lines, Some(2));
+        assert_eq!(matches[0].highlighted_lines, vec![2, 3]);
+        assert_eq!(matches[0].test_snippet[0].text, "def f():");
+        assert!(matches[0].reference_snippet.is_empty());
+        assert_eq!(matches[1].matched_lines, None);
+    }
+
+    #[test]
+    fn test_failing_score_is_not_verified() {
+        let policy = PlagiarismPolicy::default();
+        let result = PlagiarismVerificationResult::new(Some(0.95), Vec::new(), None, &policy);
+        let json = serde_json::to_value(&result.result).unwrap();
+        assert_eq!(json, serde_json::json!({ "Failed": 0.95 }));
+        let result = PlagiarismVerificationResult::new(Some(0.05), Vec::new(), None, &policy);
+        assert!(matches!(result.result, VerificationResult::Verified(_)));
+    }
+
+    #[test]
+    fn test_link_sources_builds_blob_urls() {
+        let html = r#"
+            <b>Test file:</b> /tmp/repo_1/src/a.py (82.50%)<br>
+            <b>Reference file:</b> /tmp/cd/xyz/lib/a.py (61.00%)<br>
+            <pre><span class='highlight-red'>def f():
+    return 2</span></pre>
+            <pre>x
+<span class='highlight-green'>def f():</span></pre>
+        "#;
+        let mut matches = file_matches_from_str(html);
+        link_sources(
+            &mut matches,
+            &[
+                SourceRoot {
+                    local_path: "/tmp/repo_1",
+                    url: "https://github.com/team/sub",
+                    revision: Some("abc123".to_string()),
+                },
+                SourceRoot {
+                    local_path: "/tmp/cd/xyz",
+                    url: "https://github.com/team/old.git",
+                    revision: None,
+                },
+            ],
+        );
+        assert_eq!(
+            matches[0].test_url.as_deref(),
+            Some("https://github.com/team/sub/blob/abc123/src/a.py#L1-L2")
+        );
+        assert_eq!(
+            matches[0].reference_url.as_deref(),
+            Some("https://github.com/team/old/blob/HEAD/lib/a.py#L2")
+        );
+
+        matches[0].origins = vec![SourceLocation {
+            file: "/tmp/repo_1/analysis.ipynb".to_string(),
+            section: "cell 5".to_string(),
+            start_line: 3,
+            end_line: 7,
+        }];
+        link_sources(
+            &mut matches,
+            &[SourceRoot {
+                local_path: "/tmp/repo_1",
+                url: "https://github.com/team/sub",
+                revision: Some("abc123".to_string()),
+            }],
+        );
+        assert_eq!(
+            matches[0].test_url.as_deref(),
+            Some("https://github.com/team/sub/blob/abc123/analysis.ipynb")
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use crate::plag_check::plag_result::FileMatch;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Similarity cut-offs expressed as fractions in `0.0..=1.0`. Scores below
+/// `review_threshold` pass automatically, scores at or above `fail_threshold` fail,
+/// and everything in between is flagged for manual review.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Thresholds {
+    pub review_threshold: f64,
+    pub fail_threshold: f64,
+}
+
+impl Thresholds {
+    fn classify(&self, score: f64) -> PlagiarismVerdict {
+        if score >= self.fail_threshold {
+            PlagiarismVerdict::Fail
+        } else if score >= self.review_threshold {
+            PlagiarismVerdict::Review
+        } else {
+            PlagiarismVerdict::Pass
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PlagiarismPolicy {
+    #[serde(default = "default_aggregate_thresholds")]
+    pub aggregate: Thresholds,
+    #[serde(default = "default_per_file_thresholds")]
+    pub per_file: Thresholds,
+    /// File matches with fewer highlighted lines than this are ignored by the per-file check.
+    #[serde(default)]
+    pub min_matched_lines: Option<u32>,
+}
+
+fn default_aggregate_thresholds() -> Thresholds {
+    Thresholds {
+        review_threshold: 0.2,
+        fail_threshold: 0.5,
+    }
+}
+
+fn default_per_file_thresholds() -> Thresholds {
+    Thresholds {
+        review_threshold: 0.5,
+        fail_threshold: 0.8,
+    }
+}
+
+impl Default for PlagiarismPolicy {
+    fn default() -> Self {
+        Self {
+            aggregate: default_aggregate_thresholds(),
+            per_file: default_per_file_thresholds(),
+            min_matched_lines: None,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+pub enum PlagiarismVerdict {
+    Pass,
+    Review,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PolicyOutcome {
+    pub verdict: PlagiarismVerdict,
+    pub reasons: Vec<String>,
+}
+
+/// Applies `policy` to the aggregate copydetect score (the share of submission files above
+/// the display threshold) and to the individual file matches. The overall verdict is the
+/// most severe verdict produced by either check.
+pub fn evaluate(
+    policy: &PlagiarismPolicy,
+    aggregate_score: Option<f64>,
+    matches: &[FileMatch],
+) -> PolicyOutcome {
+    let mut verdict = PlagiarismVerdict::Pass;
+    let mut reasons = Vec::new();
+
+    match aggregate_score {
+        Some(score) => {
+            let aggregate_verdict = policy.aggregate.classify(score);
+            if aggregate_verdict != PlagiarismVerdict::Pass {
+                reasons.push(format!(
+                    "{:.1}% of files are above the display threshold, at or above the aggregate {} threshold of {:.1}%",
+                    score * 100.0,
+                    threshold_name(aggregate_verdict),
+                    threshold_value(&policy.aggregate, aggregate_verdict) * 100.0
+                ));
+            }
+            verdict = verdict.max(aggregate_verdict);
+        }
+        None => {
+            reasons.push("no automated similarity score was available".to_string());
+            verdict = verdict.max(PlagiarismVerdict::Review);
+        }
+    }
+
+    for file_match in matches {
+        if let (Some(min), Some(lines)) = (policy.min_matched_lines, file_match.matched_lines)
+            && lines < min
+        {
+            continue;
+        }
+        let file_verdict = policy.per_file.classify(file_match.test_similarity);
+        if file_verdict != PlagiarismVerdict::Pass {
+            reasons.push(format!(
+                "'{}' matches '{}' at {:.1}%, at or above the per-file {} threshold of {:.1}%",
+                file_match.test_file,
+                file_match.reference_file,
+                file_match.test_similarity * 100.0,
+                threshold_name(file_verdict),
+                threshold_value(&policy.per_file, file_verdict) * 100.0
+            ));
+        }
+        verdict = verdict.max(file_verdict);
+    }
+
+    PolicyOutcome { verdict, reasons }
+}
+
+fn threshold_name(verdict: PlagiarismVerdict) -> &'static str {
+    match verdict {
+        PlagiarismVerdict::Fail => "fail",
+        _ => "review",
+    }
+}
+
+fn threshold_value(thresholds: &Thresholds, verdict: PlagiarismVerdict) -> f64 {
+    match verdict {
+        PlagiarismVerdict::Fail => thresholds.fail_threshold,
+        _ => thresholds.review_threshold,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_match(similarity: f64, matched_lines: Option<u32>) -> FileMatch {
+        FileMatch {
+            test_file: "submission/main.py".to_string(),
+            reference_file: "old/main.py".to_string(),
+            test_similarity: similarity,
+            reference_similarity: similarity,
+            token_overlap: None,
+            matched_lines,
+            origins: Vec::new(),
+            test_snippet: Vec::new(),
+            reference_snippet: Vec::new(),
+            test_url: None,
+            reference_url: None,
+            highlighted_lines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_low_score_passes() {
+        let outcome = evaluate(&PlagiarismPolicy::default(), Some(0.05), &[]);
+        assert_eq!(outcome.verdict, PlagiarismVerdict::Pass);
+        assert!(outcome.reasons.is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_thresholds() {
+        let policy = PlagiarismPolicy::default();
+        assert_eq!(
+            evaluate(&policy, Some(0.3), &[]).verdict,
+            PlagiarismVerdict::Review
+        );
+        let outcome = evaluate(&policy, Some(0.95), &[]);
+        assert_eq!(outcome.verdict, PlagiarismVerdict::Fail);
+        assert_eq!(outcome.reasons.len(), 1);
+        assert!(outcome.reasons[0].starts_with("95.0% of files are above the display threshold"));
+    }
+
+    #[test]
+    fn test_missing_score_requires_review() {
+        let outcome = evaluate(&PlagiarismPolicy::default(), None, &[]);
+        assert_eq!(outcome.verdict, PlagiarismVerdict::Review);
+    }
+
+    #[test]
+    fn test_per_file_match_escalates_verdict() {
+        let outcome = evaluate(
+            &PlagiarismPolicy::default(),
+            Some(0.05),
+            &[file_match(0.9, Some(40))],
+        );
+        assert_eq!(outcome.verdict, PlagiarismVerdict::Fail);
+        assert!(outcome.reasons[0].contains("submission/main.py"));
+    }
+
+    #[test]
+    fn test_min_matched_lines_ignores_small_matches() {
+        let policy = PlagiarismPolicy {
+            min_matched_lines: Some(10),
+            ..PlagiarismPolicy::default()
+        };
+        let outcome = evaluate(&policy, Some(0.05), &[file_match(0.9, Some(3))]);
+        assert_eq!(outcome.verdict, PlagiarismVerdict::Pass);
+    }
+}
@@ -0,0 +1,463 @@
+use crate::plag_check::plag_result::FileMatch;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Mixed-content files whose script blocks are extracted into virtual files.
+pub const SCRIPT_EXTENSIONS: &[&str] = &["vue", "svelte", "html", "htm"];
+
+/// A run of consecutive lines in a virtual file that came from one cell or section of the
+/// original file.
+#[derive(Debug, Clone)]
+pub struct SourceSegment {
+    pub virtual_start: u32,
+    pub line_count: u32,
+    pub section: String,
+    pub original_start: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct VirtualFile {
+    pub original: PathBuf,
+    pub segments: Vec<SourceSegment>,
+}
+
+/// Where lines of a virtual file originally lived, e.g. lines 3-7 of cell 5 of a notebook.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SourceLocation {
+    pub file: String,
+    pub section: String,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct PreprocessedTree {
+    pub root: PathBuf,
+    pub virtual_files: HashMap<PathBuf, VirtualFile>,
+}
+
+impl PreprocessedTree {
+    pub fn is_empty(&self) -> bool {
+        self.virtual_files.is_empty()
+    }
+
+    /// Blanks the extracted script blocks inside the original markup files, keeping every
+    /// line, so copydetect compares their markup and the virtual script files without
+    /// counting the scripts twice. The files are restored when the guard is dropped.
+    pub fn blank_scripts(&self) -> io::Result<BlankedScripts> {
+        let mut blanked = BlankedScripts {
+            originals: Vec::new(),
+        };
+        let mut originals: Vec<&PathBuf> = self
+            .virtual_files
+            .values()
+            .map(|file| &file.original)
+            .filter(|path| {
+                path.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| SCRIPT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+            })
+            .collect();
+        originals.sort();
+        originals.dedup();
+        for path in originals {
+            let contents = fs::read_to_string(path)?;
+            let stripped = blank_script_bodies(&contents);
+            blanked.originals.push((path.clone(), contents));
+            fs::write(path, stripped)?;
+        }
+        Ok(blanked)
+    }
+
+    pub fn locate(&self, virtual_path: &Path, line: u32) -> Option<SourceLocation> {
+        let file = self.virtual_files.get(virtual_path)?;
+        let segment = file
+            .segments
+            .iter()
+            .find(|s| line >= s.virtual_start && line < s.virtual_start + s.line_count)?;
+        let original_line = segment.original_start + (line - segment.virtual_start);
+        Some(SourceLocation {
+            file: file.original.display().to_string(),
+            section: segment.section.clone(),
+            start_line: original_line,
+            end_line: original_line,
+        })
+    }
+}
+
+/// Markup files whose script blocks were blanked by [`PreprocessedTree::blank_scripts`],
+/// with their original contents.
+pub struct BlankedScripts {
+    originals: Vec<(PathBuf, String)>,
+}
+
+impl Drop for BlankedScripts {
+    fn drop(&mut self) {
+        for (path, contents) in &self.originals {
+            let _ = fs::write(path, contents);
+        }
+    }
+}
+
+/// Builds one virtual file from a list of extracted sections, keeping track of which
+/// virtual lines belong to which section.
+struct VirtualFileBuilder {
+    contents: String,
+    segments: Vec<SourceSegment>,
+    next_line: u32,
+}
+
+impl VirtualFileBuilder {
+    fn new() -> Self {
+        Self {
+            contents: String::new(),
+            segments: Vec::new(),
+            next_line: 1,
+        }
+    }
+
+    fn push_section(&mut self, section: String, original_start: u32, text: &str) {
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        if lines.iter().all(|line| line.trim().is_empty()) {
+            return;
+        }
+        for line in &lines {
+            self.contents.push_str(line);
+            self.contents.push('\n');
+        }
+        self.segments.push(SourceSegment {
+            virtual_start: self.next_line,
+            line_count: lines.len() as u32,
+            section,
+            original_start,
+        });
+        // Blank separator line between sections, not attributed to either.
+        self.contents.push('\n');
+        self.next_line += lines.len() as u32 + 1;
+    }
+
+    fn write(self, path: &Path, original: &Path, tree: &mut PreprocessedTree) -> io::Result<()> {
+        if self.segments.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.contents)?;
+        tree.virtual_files.insert(
+            path.to_path_buf(),
+            VirtualFile {
+                original: original.to_path_buf(),
+                segments: self.segments,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Extracts comparable source from notebooks and mixed-content files under `source` into
+/// normalized virtual files under `target`, so copydetect compares code instead of raw
+/// notebook JSON or markup.
+pub fn preprocess_dir(source: &Path, target: &Path) -> io::Result<PreprocessedTree> {
+    let mut tree = PreprocessedTree {
+        root: target.to_path_buf(),
+        virtual_files: HashMap::new(),
+    };
+    let mut files = Vec::new();
+    collect_files(source, &mut files)?;
+
+    for file in files {
+        let Some(extension) = file.extension().and_then(|e| e.to_str()) else {
+            continue;
+        };
+        let relative = file.strip_prefix(source).unwrap_or(&file);
+        let virtual_base = target.join(relative);
+        match extension.to_ascii_lowercase().as_str() {
+            "ipynb" => {
+                let Ok(contents) = fs::read_to_string(&file) else {
+                    continue;
+                };
+                extract_notebook(&contents, &virtual_base, &file, &mut tree)?;
+            }
+            extension if SCRIPT_EXTENSIONS.contains(&extension) => {
+                let Ok(contents) = fs::read_to_string(&file) else {
+                    continue;
+                };
+                extract_scripts(&contents, &virtual_base, &file, &mut tree)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(tree)
+}
+
+/// Recursively lists regular files under `dir`, skipping `.git`.
+pub fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if entry.file_name() != ".git" {
+                collect_files(&path, files)?;
+            }
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn with_suffix(base: &Path, suffix: &str) -> PathBuf {
+    let mut name = base.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn notebook_extension(notebook: &serde_json::Value) -> &'static str {
+    let language = notebook
+        .pointer("/metadata/kernelspec/language")
+        .or_else(|| notebook.pointer("/metadata/language_info/name"))
+        .and_then(|l| l.as_str())
+        .unwrap_or("python")
+        .to_ascii_lowercase();
+    match language.as_str() {
+        "r" => "r",
+        "julia" => "jl",
+        _ => "py",
+    }
+}
+
+fn cell_source(cell: &serde_json::Value) -> String {
+    match cell.get("source") {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(parts)) => {
+            parts.iter().filter_map(|p| p.as_str()).collect::<String>()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Drops IPython magics and shell escapes, which are notebook plumbing rather than code.
+fn normalize_code_cell(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('%') || trimmed.starts_with('!') {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn extract_notebook(
+    contents: &str,
+    virtual_base: &Path,
+    original: &Path,
+    tree: &mut PreprocessedTree,
+) -> io::Result<()> {
+    let Ok(notebook) = serde_json::from_str::<serde_json::Value>(contents) else {
+        return Ok(());
+    };
+    let Some(cells) = notebook.get("cells").and_then(|c| c.as_array()) else {
+        return Ok(());
+    };
+
+    let mut code = VirtualFileBuilder::new();
+    let mut markdown = VirtualFileBuilder::new();
+    for (index, cell) in cells.iter().enumerate() {
+        let section = format!("cell {}", index + 1);
+        let source = cell_source(cell);
+        match cell.get("cell_type").and_then(|t| t.as_str()) {
+            Some("code") => code.push_section(section, 1, &normalize_code_cell(&source)),
+            Some("markdown") => markdown.push_section(section, 1, &source),
+            _ => {}
+        }
+    }
+
+    let code_extension = notebook_extension(&notebook);
+    code.write(
+        &with_suffix(virtual_base, &format!(".code.{}", code_extension)),
+        original,
+        tree,
+    )?;
+    markdown.write(&with_suffix(virtual_base, ".markdown.md"), original, tree)
+}
+
+fn script_block() -> Regex {
+    Regex::new(r#"(?is)<script([^>]*)>(.*?)</script>"#).expect("valid regex")
+}
+
+/// `contents` with the body of every script block replaced by its line breaks.
+fn blank_script_bodies(contents: &str) -> String {
+    script_block()
+        .replace_all(contents, |captures: &regex::Captures| {
+            let body = captures.get(2).map_or("", |m| m.as_str());
+            format!(
+                "<script{}>{}</script>",
+                &captures[1],
+                "\n".repeat(body.matches('\n').count())
+            )
+        })
+        .into_owned()
+}
+
+fn extract_scripts(
+    contents: &str,
+    virtual_base: &Path,
+    original: &Path,
+    tree: &mut PreprocessedTree,
+) -> io::Result<()> {
+    let script = script_block();
+    let typescript = Regex::new(r#"(?i)lang\s*=\s*["']?ts"#).expect("valid regex");
+
+    let mut builder = VirtualFileBuilder::new();
+    let mut is_typescript = false;
+    for (index, captures) in script.captures_iter(contents).enumerate() {
+        let attributes = captures.get(1).map(|m| m.as_str()).unwrap_or("");
+        let Some(body) = captures.get(2) else {
+            continue;
+        };
+        is_typescript |= typescript.is_match(attributes);
+        let start_line = contents[..body.start()].matches('\n').count() as u32 + 1;
+        // Skip the remainder of the opening tag's line so line numbers stay aligned.
+        let (text, original_start) = match body.as_str().strip_prefix('\n') {
+            Some(rest) => (rest, start_line + 1),
+            None => (body.as_str(), start_line),
+        };
+        builder.push_section(
+            format!("script block {} (line {})", index + 1, start_line),
+            original_start,
+            text,
+        );
+    }
+
+    let extension = if is_typescript { "ts" } else { "js" };
+    builder.write(
+        &with_suffix(virtual_base, &format!(".script.{}", extension)),
+        original,
+        tree,
+    )
+}
+
+/// Maps the highlighted test lines of each match back to the notebook cell or script block
+/// they were extracted from. Matches against ordinary files are left untouched.
+pub fn annotate_matches(tree: &PreprocessedTree, matches: &mut [FileMatch]) {
+    for file_match in matches.iter_mut() {
+        let path = Path::new(&file_match.test_file);
+        if !tree.virtual_files.contains_key(path) {
+            continue;
+        }
+        let mut origins: Vec<SourceLocation> = Vec::new();
+        for location in file_match
+            .highlighted_lines
+            .iter()
+            .filter_map(|line| tree.locate(path, *line))
+        {
+            match origins.last_mut() {
+                Some(last)
+                    if last.section == location.section
+                        && last.end_line + 1 == location.start_line =>
+                {
+                    last.end_line = location.end_line;
+                }
+                _ => origins.push(location),
+            }
+        }
+        file_match.origins = origins;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_temp_dir() -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("preprocess_test_{}", uuid::Uuid::new_v4()));
+        path
+    }
+
+    #[test]
+    fn test_notebook_cells_are_extracted_and_mapped() {
+        let source = unique_temp_dir();
+        let target = unique_temp_dir();
+        fs::create_dir_all(&source).unwrap();
+        let notebook = r##"{
+            "metadata": {"kernelspec": {"language": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n", "Intro"]},
+                {"cell_type": "code", "source": ["%matplotlib inline\n", "import os\n", "print(os.name)"],
+                 "outputs": [{"data": {"image/png": "iVBORw0KGgo="}}]},
+                {"cell_type": "code", "source": "x = 1\ny = 2"}
+            ]
+        }"##;
+        fs::write(source.join("analysis.ipynb"), notebook).unwrap();
+
+        let tree = preprocess_dir(&source, &target).unwrap();
+        let code_path = target.join("analysis.ipynb.code.py");
+        let code = fs::read_to_string(&code_path).unwrap();
+        assert!(code.contains("import os"));
+        assert!(!code.contains("matplotlib"));
+        assert!(!code.contains("iVBORw0KGgo"));
+        assert!(target.join("analysis.ipynb.markdown.md").exists());
+
+        // Line 5 of the virtual file is the first line of the third cell.
+        let location = tree.locate(&code_path, 5).unwrap();
+        assert_eq!(location.section, "cell 3");
+        assert_eq!(location.start_line, 1);
+
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn test_vue_script_block_keeps_original_line_numbers() {
+        let source = unique_temp_dir();
+        let target = unique_temp_dir();
+        fs::create_dir_all(&source).unwrap();
+        let vue = "<template>\n  <p>hi</p>\n</template>\n<script lang=\"ts\">\nexport default {}\nconst a = 1\n</script>\n";
+        fs::write(source.join("App.vue"), vue).unwrap();
+
+        let tree = preprocess_dir(&source, &target).unwrap();
+        let script_path = target.join("App.vue.script.ts");
+        assert!(script_path.exists());
+        let location = tree.locate(&script_path, 2).unwrap();
+        assert_eq!(location.start_line, 6);
+
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn test_blanked_scripts_keep_markup_and_are_restored() {
+        let source = unique_temp_dir();
+        let target = unique_temp_dir();
+        fs::create_dir_all(&source).unwrap();
+        let html = "<p>hi</p>\n<script>\nconst a = 1\n</script>\n<style>p {}</style>\n";
+        fs::write(source.join("index.html"), html).unwrap();
+        fs::write(source.join("plain.html"), "<p>no scripts</p>\n").unwrap();
+
+        let tree = preprocess_dir(&source, &target).unwrap();
+        let blanked = tree.blank_scripts().unwrap();
+        assert_eq!(
+            fs::read_to_string(source.join("index.html")).unwrap(),
+            "<p>hi</p>\n<script>\n\n</script>\n<style>p {}</style>\n"
+        );
+        drop(blanked);
+        assert_eq!(fs::read_to_string(source.join("index.html")).unwrap(), html);
+
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+    }
+}
@@ -1,8 +1,35 @@
-use serde::Serialize;
+use crate::plag_check::policy::PlagiarismVerdict;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize)]
+/// Similarity score from copydetect, tagged with what the plagiarism policy made of it, or
+/// `ManualRequired` when none could be computed.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "PlagiarismScore")]
 pub enum VerificationResult {
     Verified(f64),
+    NeedsReview(f64),
+    Failed(f64),
 
     ManualRequired,
 }
+
+impl VerificationResult {
+    pub fn new(score: Option<f64>, verdict: PlagiarismVerdict) -> Self {
+        match (score, verdict) {
+            (None, _) => VerificationResult::ManualRequired,
+            (Some(score), PlagiarismVerdict::Pass) => VerificationResult::Verified(score),
+            (Some(score), PlagiarismVerdict::Review) => VerificationResult::NeedsReview(score),
+            (Some(score), PlagiarismVerdict::Fail) => VerificationResult::Failed(score),
+        }
+    }
+
+    pub fn score(&self) -> Option<f64> {
+        match self {
+            VerificationResult::Verified(score)
+            | VerificationResult::NeedsReview(score)
+            | VerificationResult::Failed(score) => Some(*score),
+            VerificationResult::ManualRequired => None,
+        }
+    }
+}
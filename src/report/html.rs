@@ -0,0 +1,346 @@
+use super::{
+    MAX_COMMIT_ROWS, contributor_rows, format_bytes, format_unix, in_window, new_code_text,
+    score_text, short_id, status_text,
+};
+use crate::VerificationOutput;
+use crate::config::ConfigData;
+use crate::plag_check::plag_result::{FileMatch, SnippetLine};
+use std::fmt::Write;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;max-width:1200px}\
+table{border-collapse:collapse;margin-bottom:1em}\
+td,th{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:top}\
+.bad{color:#b00020;font-weight:bold}.ok{color:#1b5e20}\
+.side{display:flex;gap:1em}.side>div{flex:1;min-width:0}\
+pre{background:#f6f8fa;padding:8px;overflow-x:auto}";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("<a href=\"{}\">{}</a>", escape(url), escape(text)),
+        None => format!("<code>{}</code>", escape(text)),
+    }
+}
+
+fn flag(ok: bool) -> &'static str {
+    if ok {
+        "<span class=\"ok\">yes</span>"
+    } else {
+        "<span class=\"bad\">no</span>"
+    }
+}
+
+fn snippet(out: &mut String, title: &str, lines: &[SnippetLine]) {
+    let _ = write!(out, "<div><h4>{}</h4><pre>", title);
+    for line in lines {
+        let _ = writeln!(out, "{:>5} | {}", line.number, escape(&line.text));
+    }
+    let _ = write!(out, "</pre></div>");
+}
+
+fn file_match(out: &mut String, index: usize, m: &FileMatch) {
+    let _ = writeln!(
+        out,
+        "<h3>Match {}: {:.1}% of {}</h3><ul>",
+        index + 1,
+        m.test_similarity * 100.0,
+        escape(&m.test_file)
+    );
+    let _ = writeln!(
+        out,
+        "<li>Submission: {} ({:.1}%)</li><li>Reference: {} ({:.1}%)</li>",
+        link(&m.test_file, m.test_url.as_deref()),
+        m.test_similarity * 100.0,
+        link(&m.reference_file, m.reference_url.as_deref()),
+        m.reference_similarity * 100.0
+    );
+    if let Some(lines) = m.matched_lines {
+        let _ = writeln!(out, "<li>Matched lines: {}</li>", lines);
+    }
+    for origin in &m.origins {
+        let _ = writeln!(
+            out,
+            "<li>Extracted from {}, {}, lines {}-{}</li>",
+            escape(&origin.file),
+            escape(&origin.section),
+            origin.start_line,
+            origin.end_line
+        );
+    }
+    let _ = writeln!(out, "</ul>");
+    if !m.test_snippet.is_empty() || !m.reference_snippet.is_empty() {
+        let _ = write!(out, "<div class=\"side\">");
+        snippet(out, "Submission", &m.test_snippet);
+        snippet(out, "Reference", &m.reference_snippet);
+        let _ = writeln!(out, "</div>");
+    }
+}
+
+/// Renders a self-contained HTML page; styles are inlined so the file can be attached as-is.
+pub fn render(output: &VerificationOutput, config: &ConfigData) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Verification report</title><style>{}</style></head><body>",
+        STYLE
+    );
+    let _ = writeln!(out, "<h1>Verification report</h1><ul>");
+    let _ = writeln!(
+        out,
+        "<li>Repository: {}</li>",
+        link(&config.repo, Some(&config.repo))
+    );
+    let _ = writeln!(
+        out,
+        "<li>Event window: {} to {} ({}, {})</li>",
+        escape(&output.window.start),
+        escape(&output.window.end),
+        escape(&output.window.time_zone),
+        escape(&output.window.duration)
+    );
+    let _ = writeln!(
+        out,
+        "<li>Registered members: {}</li>",
+        escape(&config.usernames.join(", "))
+    );
+    let _ = writeln!(
+        out,
+        "<li>Overall verdict: <b>{:?}</b>",
+        output.outcome.verdict
+    );
+    if !output.outcome.reasons.is_empty() {
+        let _ = writeln!(out, "<ul>");
+        for reason in &output.outcome.reasons {
+            let _ = writeln!(out, "<li>{}</li>", escape(reason));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+    let _ = writeln!(out, "</li></ul>");
+
+    let metadata = &output.metadata;
+    let _ = writeln!(
+        out,
+        "<h2>Metadata</h2><table><tr><th>Check</th><th>Result</th></tr>"
+    );
+    for (name, result) in [
+        ("First commit time", &metadata.first_commit_time),
+        ("Last commit time", &metadata.last_commit_time),
+        ("Contributors", &metadata.contributors),
+    ]
+    .into_iter()
+    .chain(metadata.provenance.checks())
+    {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            name,
+            escape(&status_text(result))
+        );
+    }
+    let _ = writeln!(out, "</table>");
+    if !metadata.windows.is_empty() {
+        let _ = writeln!(
+            out,
+            "<h3>Commit windows</h3><table><tr><th>Window</th><th>Start</th><th>End</th><th>Authors</th><th>First commit</th><th>Last commit</th></tr>"
+        );
+        for window in &metadata.windows {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&window.window),
+                format_unix(window.start_time),
+                format_unix(window.end_time),
+                escape(&window.authors.join(", ")),
+                format_unix(window.first_commit),
+                format_unix(window.last_commit)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+    let _ = writeln!(out, "<h3>Commits</h3>");
+    if metadata.commits.is_empty() {
+        let _ = writeln!(out, "<p>No commits could be read.</p>");
+    } else {
+        let _ = writeln!(
+            out,
+            "<table><tr><th>Commit</th><th>Author</th><th>Time</th><th>In window</th><th>Summary</th></tr>"
+        );
+        for commit in metadata.commits.iter().take(MAX_COMMIT_ROWS) {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                short_id(&commit.id),
+                escape(&commit.author),
+                format_unix(commit.time),
+                flag(in_window(config, commit)),
+                escape(&commit.summary)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+        if metadata.commits.len() > MAX_COMMIT_ROWS {
+            let _ = writeln!(
+                out,
+                "<p>{} older commits not shown.</p>",
+                metadata.commits.len() - MAX_COMMIT_ROWS
+            );
+        }
+    }
+
+    let _ = writeln!(out, "<h2>Contributors</h2>");
+    let rows = contributor_rows(config, metadata);
+    if rows.is_empty() {
+        let _ = writeln!(out, "<p>No contributors found.</p>");
+    } else {
+        let _ = writeln!(
+            out,
+            "<table><tr><th>Author</th><th>Emails</th><th>Commits</th><th>In window</th><th>First</th><th>Last</th><th>Registered</th></tr>"
+        );
+        for row in rows {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&row.author),
+                escape(&row.emails.join(", ")),
+                row.commits,
+                row.commits_in_window,
+                format_unix(row.first),
+                format_unix(row.last),
+                if row.allowlisted && !row.registered {
+                    "allowlisted"
+                } else {
+                    flag(row.registered)
+                }
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+    if !metadata.allowlisted.is_empty() {
+        let _ = writeln!(
+            out,
+            "<h3>Allowlisted authors</h3><table><tr><th>Author</th><th>Matched</th><th>Commits</th><th>Added</th><th>Removed</th></tr>"
+        );
+        for bot in &metadata.allowlisted {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td>+{}</td><td>-{}</td></tr>",
+                escape(&bot.name),
+                escape(&bot.rule),
+                bot.commits,
+                bot.insertions,
+                bot.deletions
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+
+    if let Some(new_code) = &metadata.new_code {
+        let _ = writeln!(
+            out,
+            "<h3>New code</h3><p>{} of {} surviving lines at <code>{}</code> were written during the event; {} files were set aside as vendored, generated, or not blamable.</p>",
+            new_code_text(new_code),
+            new_code.lines,
+            short_id(&new_code.commit),
+            new_code.excluded.len()
+        );
+        if !new_code.by_author.is_empty() {
+            let _ = writeln!(
+                out,
+                "<table><tr><th>Author</th><th>Lines</th><th>New lines</th></tr>"
+            );
+            for share in &new_code.by_author {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape(&share.name),
+                    share.lines,
+                    share.new_lines
+                );
+            }
+            let _ = writeln!(out, "</table>");
+        }
+    }
+
+    if let Some(prior_work) = &metadata.prior_work {
+        let _ = writeln!(out, "<h3>Prior work</h3><ul>");
+        for delta in &prior_work.declared {
+            let _ = writeln!(out, "<li>{}</li>", escape(&delta.summary()));
+        }
+        let _ = writeln!(
+            out,
+            "<li>{} commits before the event are not part of any declared prior work</li></ul>",
+            prior_work.undeclared.len()
+        );
+    }
+
+    let _ = writeln!(out, "<h2>GitHub issues</h2>");
+    if output.github_issues.is_empty() {
+        let _ = writeln!(out, "<p>None.</p>");
+    } else {
+        let _ = writeln!(out, "<ul>");
+        for issue in &output.github_issues {
+            let retry = if issue.retryable { " (retryable)" } else { "" };
+            let _ = writeln!(out, "<li>{}{}</li>", escape(&issue.message), retry);
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+
+    let plagiarism = &output.plagiarism;
+    let _ = writeln!(out, "<h2>Plagiarism</h2><ul>");
+    let _ = writeln!(
+        out,
+        "<li>Files above display threshold: {}</li><li>Verdict: <b>{:?}</b>",
+        score_text(&plagiarism.result),
+        plagiarism.policy.verdict
+    );
+    if !plagiarism.policy.reasons.is_empty() {
+        let _ = writeln!(out, "<ul>");
+        for reason in &plagiarism.policy.reasons {
+            let _ = writeln!(out, "<li>{}</li>", escape(reason));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+    let _ = writeln!(out, "</li>");
+    if plagiarism.report_path.is_some() {
+        let _ = writeln!(
+            out,
+            "<li>Full copydetect report: <a href=\"report.html\">report.html</a></li>"
+        );
+    }
+    let _ = writeln!(out, "</ul>");
+    for (index, m) in plagiarism.matches.iter().enumerate() {
+        file_match(&mut out, index, m);
+    }
+
+    let _ = writeln!(out, "<h2>Duplicate assets</h2>");
+    if output.assets.matches.is_empty() {
+        let _ = writeln!(out, "<p>None.</p>");
+    } else {
+        let _ = writeln!(
+            out,
+            "<table><tr><th>Kind</th><th>Submission file</th><th>Size</th><th>Reference</th><th>Size</th></tr>"
+        );
+        for asset in &output.assets.matches {
+            let _ = writeln!(
+                out,
+                "<tr><td>{:?}</td><td><code>{}</code></td><td>{}</td><td>{} <code>{}</code></td><td>{}</td></tr>",
+                asset.kind,
+                escape(&asset.submission_path),
+                format_bytes(asset.submission_size_bytes),
+                link(&asset.reference_repo, Some(&asset.reference_repo)),
+                escape(&asset.reference_path),
+                format_bytes(asset.reference_size_bytes)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+
+    let _ = writeln!(out, "</body></html>");
+    out
+}
@@ -0,0 +1,292 @@
+use super::{
+    ContributorRow, MAX_COMMIT_ROWS, contributor_rows, format_bytes, format_unix, in_window,
+    new_code_text, score_text, short_id, status_text,
+};
+use crate::VerificationOutput;
+use crate::config::ConfigData;
+use crate::plag_check::plag_result::{FileMatch, SnippetLine};
+use std::fmt::Write;
+
+fn registered(row: &ContributorRow) -> &'static str {
+    if row.registered {
+        "yes"
+    } else if row.allowlisted {
+        "allowlisted"
+    } else {
+        "**no**"
+    }
+}
+
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("[{}]({})", cell(text), url),
+        None => format!("`{}`", cell(text)),
+    }
+}
+
+fn snippet(out: &mut String, title: &str, lines: &[SnippetLine]) {
+    if lines.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "{}:\n\n```", title);
+    for line in lines {
+        let _ = writeln!(out, "{:>5} | {}", line.number, line.text);
+    }
+    let _ = writeln!(out, "```\n");
+}
+
+fn file_match(out: &mut String, index: usize, m: &FileMatch) {
+    let _ = writeln!(
+        out,
+        "### Match {}: {:.1}% of {}\n",
+        index + 1,
+        m.test_similarity * 100.0,
+        cell(&m.test_file)
+    );
+    let _ = writeln!(
+        out,
+        "- Submission: {} ({:.1}%)",
+        link(&m.test_file, m.test_url.as_deref()),
+        m.test_similarity * 100.0
+    );
+    let _ = writeln!(
+        out,
+        "- Reference: {} ({:.1}%)",
+        link(&m.reference_file, m.reference_url.as_deref()),
+        m.reference_similarity * 100.0
+    );
+    if let Some(lines) = m.matched_lines {
+        let _ = writeln!(out, "- Matched lines: {}", lines);
+    }
+    for origin in &m.origins {
+        let _ = writeln!(
+            out,
+            "- Extracted from {}, {}, lines {}-{}",
+            cell(&origin.file),
+            origin.section,
+            origin.start_line,
+            origin.end_line
+        );
+    }
+    let _ = writeln!(out);
+    snippet(out, "Submission", &m.test_snippet);
+    snippet(out, "Reference", &m.reference_snippet);
+}
+
+pub fn render(output: &VerificationOutput, config: &ConfigData) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Verification report\n");
+    let _ = writeln!(out, "- Repository: <{}>", config.repo);
+    let _ = writeln!(
+        out,
+        "- Event window: {} to {} ({}, {})",
+        output.window.start, output.window.end, output.window.time_zone, output.window.duration
+    );
+    let _ = writeln!(out, "- Registered members: {}", config.usernames.join(", "));
+    let _ = writeln!(out, "- Overall verdict: **{:?}**\n", output.outcome.verdict);
+    for reason in &output.outcome.reasons {
+        let _ = writeln!(out, "  - {}", cell(reason));
+    }
+    if !output.outcome.reasons.is_empty() {
+        let _ = writeln!(out);
+    }
+
+    let metadata = &output.metadata;
+    let _ = writeln!(out, "## Metadata\n");
+    let _ = writeln!(out, "| Check | Result |\n| --- | --- |");
+    for (name, result) in [
+        ("First commit time", &metadata.first_commit_time),
+        ("Last commit time", &metadata.last_commit_time),
+        ("Contributors", &metadata.contributors),
+    ]
+    .into_iter()
+    .chain(metadata.provenance.checks())
+    {
+        let _ = writeln!(out, "| {} | {} |", name, cell(&status_text(result)));
+    }
+
+    if !metadata.windows.is_empty() {
+        let _ = writeln!(out, "\n### Commit windows\n");
+        let _ = writeln!(
+            out,
+            "| Window | Start | End | Authors | First commit | Last commit |\n| --- | --- | --- | --- | --- | --- |"
+        );
+        for window in &metadata.windows {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                cell(&window.window),
+                format_unix(window.start_time),
+                format_unix(window.end_time),
+                cell(&window.authors.join(", ")),
+                format_unix(window.first_commit),
+                format_unix(window.last_commit)
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n### Commits\n");
+    if metadata.commits.is_empty() {
+        let _ = writeln!(out, "No commits could be read.");
+    } else {
+        let _ = writeln!(
+            out,
+            "| Commit | Author | Time | In window | Summary |\n| --- | --- | --- | --- | --- |"
+        );
+        for commit in metadata.commits.iter().take(MAX_COMMIT_ROWS) {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {} |",
+                short_id(&commit.id),
+                cell(&commit.author),
+                format_unix(commit.time),
+                if in_window(config, commit) {
+                    "yes"
+                } else {
+                    "**no**"
+                },
+                cell(&commit.summary)
+            );
+        }
+        if metadata.commits.len() > MAX_COMMIT_ROWS {
+            let _ = writeln!(
+                out,
+                "\n{} older commits not shown.",
+                metadata.commits.len() - MAX_COMMIT_ROWS
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n## Contributors\n");
+    let rows = contributor_rows(config, metadata);
+    if rows.is_empty() {
+        let _ = writeln!(out, "No contributors found.");
+    } else {
+        let _ = writeln!(
+            out,
+            "| Author | Emails | Commits | In window | First | Last | Registered |\n| --- | --- | --- | --- | --- | --- | --- |"
+        );
+        for row in rows {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                cell(&row.author),
+                cell(&row.emails.join(", ")),
+                row.commits,
+                row.commits_in_window,
+                format_unix(row.first),
+                format_unix(row.last),
+                registered(&row)
+            );
+        }
+    }
+    if !metadata.allowlisted.is_empty() {
+        let _ = writeln!(
+            out,
+            "\n### Allowlisted authors\n\n| Author | Matched | Commits | Added | Removed |\n| --- | --- | --- | --- | --- |"
+        );
+        for bot in &metadata.allowlisted {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {} | +{} | -{} |",
+                cell(&bot.name),
+                cell(&bot.rule),
+                bot.commits,
+                bot.insertions,
+                bot.deletions
+            );
+        }
+    }
+
+    if let Some(new_code) = &metadata.new_code {
+        let _ = writeln!(out, "\n### New code\n");
+        let _ = writeln!(
+            out,
+            "{} of {} surviving lines at `{}` were written during the event; {} files were set aside as vendored, generated, or not blamable.",
+            new_code_text(new_code),
+            new_code.lines,
+            short_id(&new_code.commit),
+            new_code.excluded.len()
+        );
+        if !new_code.by_author.is_empty() {
+            let _ = writeln!(out, "\n| Author | Lines | New lines |\n| --- | --- | --- |");
+            for share in &new_code.by_author {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    cell(&share.name),
+                    share.lines,
+                    share.new_lines
+                );
+            }
+        }
+    }
+
+    if let Some(prior_work) = &metadata.prior_work {
+        let _ = writeln!(out, "\n### Prior work\n");
+        for delta in &prior_work.declared {
+            let _ = writeln!(out, "- {}", delta.summary());
+        }
+        let _ = writeln!(
+            out,
+            "- {} commits before the event are not part of any declared prior work",
+            prior_work.undeclared.len()
+        );
+    }
+
+    let _ = writeln!(out, "\n## GitHub issues\n");
+    if output.github_issues.is_empty() {
+        let _ = writeln!(out, "None.");
+    }
+    for issue in &output.github_issues {
+        let retry = if issue.retryable { " (retryable)" } else { "" };
+        let _ = writeln!(out, "- {}{}", issue, retry);
+    }
+
+    let plagiarism = &output.plagiarism;
+    let _ = writeln!(out, "\n## Plagiarism\n");
+    let _ = writeln!(
+        out,
+        "- Files above display threshold: {}",
+        score_text(&plagiarism.result)
+    );
+    let _ = writeln!(out, "- Verdict: **{:?}**", plagiarism.policy.verdict);
+    for reason in &plagiarism.policy.reasons {
+        let _ = writeln!(out, "  - {}", reason);
+    }
+    if plagiarism.report_path.is_some() {
+        let _ = writeln!(out, "- Full copydetect report: [report.html](report.html)");
+    }
+    let _ = writeln!(out);
+    for (index, m) in plagiarism.matches.iter().enumerate() {
+        file_match(&mut out, index, m);
+    }
+
+    let _ = writeln!(out, "## Duplicate assets\n");
+    if output.assets.matches.is_empty() {
+        let _ = writeln!(out, "None.");
+    } else {
+        let _ = writeln!(
+            out,
+            "| Kind | Submission file | Size | Reference | Size |\n| --- | --- | --- | --- | --- |"
+        );
+        for asset in &output.assets.matches {
+            let _ = writeln!(
+                out,
+                "| {:?} | `{}` | {} | {} `{}` | {} |",
+                asset.kind,
+                cell(&asset.submission_path),
+                format_bytes(asset.submission_size_bytes),
+                asset.reference_repo,
+                cell(&asset.reference_path),
+                format_bytes(asset.reference_size_bytes)
+            );
+        }
+    }
+
+    out
+}
@@ -0,0 +1,244 @@
+pub mod html;
+pub mod markdown;
+
+use crate::VerificationOutput;
+use crate::config::ConfigData;
+use crate::git_tools::bots::is_member;
+use crate::git_tools::metadata::{CommitSummary, MetadataVerificationResult};
+use crate::git_tools::new_code::NewCodeResult;
+use crate::git_tools::verification::VerificationResult;
+use crate::plag_check::verification::VerificationResult as PlagiarismScore;
+use chrono::{TimeZone, Utc};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Commit tables are cut off after this many rows; the contributor breakdown still counts
+/// every commit.
+const MAX_COMMIT_ROWS: usize = 200;
+
+/// Commits and time range attributed to one author name.
+struct ContributorRow {
+    author: String,
+    emails: Vec<String>,
+    commits: usize,
+    commits_in_window: usize,
+    first: i64,
+    last: i64,
+    registered: bool,
+    /// Accepted by the bot allowlist.
+    allowlisted: bool,
+}
+
+/// Writes `summary.html` and `summary.md` into `output_dir`.
+pub fn write_reports(
+    output: &VerificationOutput,
+    config: &ConfigData,
+    output_dir: &Path,
+) -> io::Result<()> {
+    fs::write(
+        output_dir.join("summary.html"),
+        html::render(output, config),
+    )?;
+    fs::write(
+        output_dir.join("summary.md"),
+        markdown::render(output, config),
+    )?;
+    Ok(())
+}
+
+fn format_unix(secs: i64) -> String {
+    match Utc.timestamp_opt(secs, 0).single() {
+        Some(dt) => dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => format!("{} (invalid timestamp)", secs),
+    }
+}
+
+/// Whether a commit falls inside its author's window.
+fn in_window(config: &ConfigData, commit: &CommitSummary) -> bool {
+    let (start, end) = config.window_of(&commit.author, &commit.email);
+    commit.time >= start as i64 && commit.time < end as i64
+}
+
+fn status_text(result: &VerificationResult) -> String {
+    match result {
+        VerificationResult::Verified => "Verified".to_string(),
+        VerificationResult::Skipped => "Skipped".to_string(),
+        VerificationResult::Failed(reason) => format!("Failed: {}", reason),
+    }
+}
+
+fn score_text(result: &PlagiarismScore) -> String {
+    match result.score() {
+        Some(score) => format!("{:.1}%", score * 100.0),
+        None => "not available (manual review required)".to_string(),
+    }
+}
+
+fn new_code_text(new_code: &NewCodeResult) -> String {
+    match new_code.fraction {
+        Some(fraction) => format!("{:.1}% ({})", fraction * 100.0, new_code.new_lines),
+        None => "None".to_string(),
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
+fn contributor_rows(
+    config: &ConfigData,
+    metadata: &MetadataVerificationResult,
+) -> Vec<ContributorRow> {
+    let mut rows: Vec<ContributorRow> = Vec::new();
+    for commit in &metadata.commits {
+        let row = match rows.iter_mut().position(|r| r.author == commit.author) {
+            Some(index) => &mut rows[index],
+            None => {
+                rows.push(ContributorRow {
+                    author: commit.author.clone(),
+                    emails: Vec::new(),
+                    commits: 0,
+                    commits_in_window: 0,
+                    first: commit.time,
+                    last: commit.time,
+                    registered: false,
+                    allowlisted: metadata
+                        .allowlisted
+                        .iter()
+                        .any(|bot| bot.name == commit.author),
+                });
+                rows.last_mut().expect("just pushed")
+            }
+        };
+        if !commit.email.is_empty() && !row.emails.contains(&commit.email) {
+            row.emails.push(commit.email.clone());
+        }
+        row.registered |= is_member(&commit.author, &commit.email, &config.usernames);
+        row.commits += 1;
+        if in_window(config, commit) {
+            row.commits_in_window += 1;
+        }
+        row.first = row.first.min(commit.time);
+        row.last = row.last.max(commit.time);
+    }
+    rows.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.author.cmp(&b.author)));
+    rows
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_tools::bots::AllowlistedAuthor;
+    use crate::git_tools::github_issue::{GithubIssue, GithubIssueKind};
+    use crate::git_tools::new_code::NewCodeVerdict;
+    use crate::plag_check::assets::AssetCheckResult;
+    use crate::plag_check::plag_result::PlagiarismVerificationResult;
+
+    fn sample() -> (VerificationOutput, ConfigData) {
+        let config: ConfigData = serde_json::from_str(
+            r#"{"repo": "https://github.com/team/sub", "usernames": ["alice"],
+                "start_time": 1000, "end_time": 2000}"#,
+        )
+        .unwrap();
+        let mut metadata = MetadataVerificationResult::new(
+            VerificationResult::Verified,
+            VerificationResult::Verified,
+            VerificationResult::Skipped,
+        );
+        for (id, author, time) in [("a1", "alice", 1500), ("b2", "<mallory>", 2500)] {
+            metadata.commits.push(CommitSummary {
+                id: id.to_string(),
+                author: author.to_string(),
+                email: format!("{}@example.com", id),
+                time,
+                summary: "work | more".to_string(),
+            });
+        }
+        metadata.allowlisted.push(AllowlistedAuthor {
+            name: "dependabot[bot]".to_string(),
+            email: "49699333+dependabot[bot]@users.noreply.github.com".to_string(),
+            rule: "*[bot]@users.noreply.github.com".to_string(),
+            commits: 2,
+            insertions: 40,
+            deletions: 12,
+        });
+        metadata.new_code = Some(NewCodeResult {
+            commit: "a1".to_string(),
+            lines: 200,
+            new_lines: 50,
+            prior_lines: 0,
+            fraction: Some(0.25),
+            by_author: Vec::new(),
+            by_file: Vec::new(),
+            excluded: vec!["package-lock.json".to_string()],
+            blame_errors: Vec::new(),
+            verdict: NewCodeVerdict::Pass,
+            reason: None,
+        });
+        let plagiarism = PlagiarismVerificationResult::manual(None, &Default::default());
+        let assets = AssetCheckResult::default();
+        let github_issues = vec![GithubIssue {
+            kind: GithubIssueKind::UserNotFound,
+            subject: "bob".to_string(),
+            http_status: Some(404),
+            retryable: false,
+            message: "Failed to list repos for user 'bob'".to_string(),
+        }];
+        let output = VerificationOutput {
+            schema_version: crate::schema::SCHEMA_VERSION,
+            outcome: crate::outcome::evaluate(&metadata, &plagiarism, &assets, &github_issues),
+            window: config.window(),
+            config: config.clone(),
+            metadata,
+            plagiarism,
+            assets,
+            github_issues,
+        };
+        (output, config)
+    }
+
+    #[test]
+    fn test_markdown_report_covers_all_sections() {
+        let (output, config) = sample();
+        let markdown = markdown::render(&output, &config);
+        for heading in [
+            "## Metadata",
+            "### Commits",
+            "## Contributors",
+            "### Allowlisted authors",
+            "### New code",
+            "## GitHub issues",
+            "## Plagiarism",
+            "## Duplicate assets",
+        ] {
+            assert!(markdown.contains(heading), "missing {}", heading);
+        }
+        assert!(markdown.contains("work \\| more"));
+        assert!(markdown.contains("Failed to list repos for user 'bob'"));
+        assert!(
+            markdown.contains(
+                "| dependabot[bot] | `*[bot]@users.noreply.github.com` | 2 | +40 | -12 |"
+            )
+        );
+        assert!(markdown.contains("25.0% (50) of 200 surviving lines"));
+    }
+
+    #[test]
+    fn test_html_report_escapes_user_content() {
+        let (output, config) = sample();
+        let html = html::render(&output, &config);
+        assert!(html.contains("&lt;mallory&gt;"));
+        assert!(!html.contains("<mallory>"));
+    }
+}
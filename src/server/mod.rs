@@ -0,0 +1,432 @@
+use crate::config::profile::{is_url, resolve_fields};
+use crate::config::validate::{ConfigIssue, validate};
+use crate::config::{ConfigData, config_from_fields};
+use crate::outcome::Verdict;
+use crate::workspace::WorkdirOptions;
+use crate::{OUTPUT_FILES, verify_submission};
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Semaphore;
+use tracing::{info, info_span, warn};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Finished,
+    /// The pipeline could not complete; see `error`. A submission that fails verification
+    /// is `Finished` with a `Failed` verdict instead.
+    Failed,
+}
+
+/// A verification request as reported by the status endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub status: JobStatus,
+    pub repo: String,
+    pub submitted_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<Verdict>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Result files that can be downloaded from `/jobs/{id}/files/{name}`.
+    pub files: Vec<String>,
+}
+
+pub struct ServeOptions {
+    pub listen: SocketAddr,
+    /// Number of submissions verified at the same time; further jobs wait in order.
+    pub concurrency: usize,
+    /// Each job writes its results to `<output>/<job id>/`.
+    pub output: PathBuf,
+    /// Directory that `event` profiles named by submitted configs are resolved against.
+    pub events_dir: PathBuf,
+}
+
+pub struct AppState {
+    jobs: Mutex<HashMap<Uuid, Job>>,
+    slots: Semaphore,
+    workdir: WorkdirOptions,
+    output: PathBuf,
+    events_dir: PathBuf,
+}
+
+type Shared = Arc<AppState>;
+
+impl AppState {
+    fn new(options: &ServeOptions, workdir: WorkdirOptions) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            slots: Semaphore::new(options.concurrency.max(1)),
+            workdir,
+            output: options.output.clone(),
+            events_dir: options.events_dir.clone(),
+        }
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, HashMap<Uuid, Job>> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn update(&self, id: Uuid, change: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs().get_mut(&id) {
+            change(job);
+        }
+    }
+
+    fn job_dir(&self, id: Uuid) -> PathBuf {
+        self.output.join(id.to_string())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    issues: Vec<ConfigIssue>,
+}
+
+type Rejection = (StatusCode, Json<ApiError>);
+
+fn rejection(status: StatusCode, error: impl Into<String>) -> Rejection {
+    let body = ApiError {
+        error: error.into(),
+        issues: Vec::new(),
+    };
+    (status, Json(body))
+}
+
+fn reject(status: StatusCode, error: impl Into<String>) -> Response {
+    rejection(status, error).into_response()
+}
+
+/// Fields only the server's event profiles may set: a ledger is a server file, and a webhook
+/// would let clients make the server send requests and sign them with any of its
+/// environment variables.
+const SERVER_FIELDS: &[&str] = &["ledger", "webhook"];
+
+/// Clients may only name profiles inside the events directory, not arbitrary server files,
+/// and may not point at other server files or directories either.
+fn check_client_fields(fields: &Map<String, Value>) -> Result<(), String> {
+    if let Some(field) = SERVER_FIELDS.iter().find(|f| fields.contains_key(**f)) {
+        return Err(format!(
+            "`{}` cannot be submitted; it is set by the server's event profile.",
+            field
+        ));
+    }
+    let repos = |field: &str| -> Vec<String> {
+        let entries = match fields.get(field) {
+            Some(Value::Array(entries)) => entries.as_slice(),
+            _ => &[],
+        };
+        entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Value::String(path) => Some(path.clone()),
+                Value::Object(entry) => entry.get("repo")?.as_str().map(str::to_string),
+                _ => None,
+            })
+            .collect()
+    };
+    for field in ["boilerplate", "prior_work"] {
+        if let Some(path) = repos(field).iter().find(|path| !is_url(path)) {
+            return Err(format!(
+                "`{}` entries must be repository URLs, not server paths ('{}').",
+                field, path
+            ));
+        }
+    }
+    if let Some(Value::String(event)) = fields.get("event") {
+        let path = Path::new(event);
+        if path.is_absolute()
+            || path
+                .components()
+                .any(|component| matches!(component, Component::ParentDir))
+        {
+            return Err(format!(
+                "`event` ('{}') must be a path inside the server's events directory.",
+                event
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Turns a submitted config into `ConfigData`, with the same merging and validation as a
+/// config file given on the command line.
+fn parse_submission(
+    events_dir: &Path,
+    fields: Map<String, Value>,
+) -> Result<ConfigData, Rejection> {
+    let bad_request = |err| rejection(StatusCode::BAD_REQUEST, err);
+    check_client_fields(&fields).map_err(bad_request)?;
+    let fields = resolve_fields(fields, events_dir).map_err(bad_request)?;
+    let data = config_from_fields(fields, "The submitted config").map_err(bad_request)?;
+    let issues = validate(&data);
+    if !issues.is_empty() {
+        let body = ApiError {
+            error: format!("The submitted config has {} problem(s).", issues.len()),
+            issues,
+        };
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(body)));
+    }
+    Ok(data)
+}
+
+async fn submit(State(state): State<Shared>, Json(fields): Json<Map<String, Value>>) -> Response {
+    let data = match parse_submission(&state.events_dir, fields) {
+        Ok(data) => data,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let job = Job {
+        id: Uuid::new_v4(),
+        status: JobStatus::Queued,
+        repo: data.repo.clone(),
+        submitted_at: chrono::Utc::now().to_rfc3339(),
+        verdict: None,
+        error: None,
+        files: Vec::new(),
+    };
+    state.jobs().insert(job.id, job.clone());
+    info!(job = %job.id, repo = %job.repo, "Queued verification");
+    tokio::spawn(run_job(state.clone(), job.id, data));
+
+    let location = format!("/jobs/{}", job.id);
+    (
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, location)],
+        Json(job),
+    )
+        .into_response()
+}
+
+/// Waits for a free slot, then runs the regular pipeline for the job. The pipeline clones
+/// and runs copydetect synchronously, so it gets a blocking thread of its own and does not
+/// stall the HTTP handlers.
+async fn run_job(state: Shared, id: Uuid, data: ConfigData) {
+    let Ok(_permit) = state.slots.acquire().await else {
+        return;
+    };
+    state.update(id, |job| job.status = JobStatus::Running);
+
+    let output_dir = state.job_dir(id);
+    let pipeline_state = state.clone();
+    let pipeline_dir = output_dir.clone();
+    let span = info_span!("job", id = %id, repo = %data.repo);
+    let result = tokio::task::spawn_blocking(move || {
+        span.in_scope(|| {
+            tokio::runtime::Handle::current()
+                .block_on(verify_submission(
+                    &data,
+                    &pipeline_state.workdir,
+                    &pipeline_dir,
+                ))
+                .map(|output| output.outcome.verdict)
+                .map_err(|err| err.to_string())
+        })
+    })
+    .await;
+
+    let files: Vec<String> = OUTPUT_FILES
+        .iter()
+        .filter(|name| output_dir.join(name).exists())
+        .map(|name| name.to_string())
+        .collect();
+    state.update(id, |job| {
+        job.files = files;
+        match result {
+            Ok(Ok(verdict)) => {
+                job.status = JobStatus::Finished;
+                job.verdict = Some(verdict);
+            }
+            Ok(Err(err)) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(err);
+            }
+            Err(err) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(format!("The verification task crashed: {}", err));
+            }
+        }
+    });
+    if let Some(job) = state.jobs().get(&id) {
+        match (&job.error, job.verdict) {
+            (Some(err), _) => warn!(job = %id, "Verification failed: {}", err),
+            (None, Some(verdict)) => info!(job = %id, ?verdict, "Verification finished"),
+            (None, None) => {}
+        }
+    }
+}
+
+async fn list_jobs(State(state): State<Shared>) -> Json<Vec<Job>> {
+    let mut jobs: Vec<Job> = state.jobs().values().cloned().collect();
+    jobs.sort_by(|a, b| a.submitted_at.cmp(&b.submitted_at));
+    Json(jobs)
+}
+
+async fn job_status(State(state): State<Shared>, UrlPath(id): UrlPath<Uuid>) -> Response {
+    match state.jobs().get(&id) {
+        Some(job) => Json(job.clone()).into_response(),
+        None => reject(StatusCode::NOT_FOUND, format!("No job with ID {}.", id)),
+    }
+}
+
+fn content_type(name: &str) -> &'static str {
+    match Path::new(name).extension().and_then(|e| e.to_str()) {
+        Some("json") => "application/json",
+        Some("html") => "text/html; charset=utf-8",
+        Some("md") => "text/markdown; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+async fn job_file(
+    State(state): State<Shared>,
+    UrlPath((id, name)): UrlPath<(Uuid, String)>,
+) -> Response {
+    let Some(job) = state.jobs().get(&id).cloned() else {
+        return reject(StatusCode::NOT_FOUND, format!("No job with ID {}.", id));
+    };
+    if !OUTPUT_FILES.contains(&name.as_str()) {
+        return reject(
+            StatusCode::NOT_FOUND,
+            format!(
+                "Unknown file '{}'; expected one of {:?}.",
+                name, OUTPUT_FILES
+            ),
+        );
+    }
+    if matches!(job.status, JobStatus::Queued | JobStatus::Running) {
+        return reject(
+            StatusCode::CONFLICT,
+            format!("Job {} has not finished yet.", id),
+        );
+    }
+    match fs::read(state.job_dir(id).join(&name)) {
+        Ok(contents) => ([(header::CONTENT_TYPE, content_type(&name))], contents).into_response(),
+        Err(_) => reject(
+            StatusCode::NOT_FOUND,
+            format!("Job {} did not produce {}.", id, name),
+        ),
+    }
+}
+
+async fn job_result(State(state): State<Shared>, UrlPath(id): UrlPath<Uuid>) -> Response {
+    job_file(State(state), UrlPath((id, "result.json".to_string()))).await
+}
+
+fn router(state: Shared) -> Router {
+    Router::new()
+        .route("/jobs", post(submit).get(list_jobs))
+        .route("/jobs/{id}", get(job_status))
+        .route("/jobs/{id}/result", get(job_result))
+        .route("/jobs/{id}/files/{name}", get(job_file))
+        .with_state(state)
+}
+
+/// Serves the job API until the process is stopped. Jobs are kept in memory; their result
+/// files stay in `options.output`.
+pub async fn serve(
+    options: ServeOptions,
+    workdir: WorkdirOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(&options.output)?;
+    let state = Arc::new(AppState::new(&options, workdir));
+    let listener = tokio::net::TcpListener::bind(options.listen)
+        .await
+        .map_err(|err| format!("Could not listen on {}: {}", options.listen, err))?;
+    info!(
+        concurrency = options.concurrency,
+        "Listening on http://{}", options.listen
+    );
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> Shared {
+        let options = ServeOptions {
+            listen: "127.0.0.1:0".parse().unwrap(),
+            concurrency: 1,
+            output: std::env::temp_dir().join("terrier_server_test"),
+            events_dir: PathBuf::from("."),
+        };
+        let workdir = WorkdirOptions {
+            root: std::env::temp_dir(),
+            keep: false,
+        };
+        Arc::new(AppState::new(&options, workdir))
+    }
+
+    fn fields(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_invalid_submissions_are_rejected_without_a_job() {
+        let state = state();
+        let invalid = fields(serde_json::json!({
+            "repo": "https://github.com/a/b",
+            "usernames": [],
+            "start_time": 1769280135,
+            "end_time": 1769409735
+        }));
+        let response = submit(State(state.clone()), Json(invalid)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        for escaping in [
+            serde_json::json!({"event": "../secrets.toml"}),
+            serde_json::json!({"ledger": "/etc/passwd"}),
+            serde_json::json!({"webhook": {"url": "http://169.254.169.254/", "secret_env": "GITHUB_TOKEN"}}),
+            serde_json::json!({"prior_work": [{"repo": "/srv/other-team"}]}),
+            serde_json::json!({"boilerplate": ["/home"]}),
+        ] {
+            let response = submit(State(state.clone()), Json(fields(escaping))).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        assert!(state.jobs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_files_of_unfinished_or_unknown_jobs() {
+        let state = state();
+        let id = Uuid::new_v4();
+        let response = job_status(State(state.clone()), UrlPath(id)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        state.jobs().insert(
+            id,
+            Job {
+                id,
+                status: JobStatus::Running,
+                repo: "https://github.com/a/b".to_string(),
+                submitted_at: String::new(),
+                verdict: None,
+                error: None,
+                files: Vec::new(),
+            },
+        );
+        let response = job_result(State(state.clone()), UrlPath(id)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = job_file(State(state), UrlPath((id, "../data.json".to_string()))).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,198 @@
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Header with the hex HMAC-SHA256 of the request body, prefixed with `sha256=`.
+pub const SIGNATURE_HEADER: &str = "X-Terrier-Signature-256";
+/// Header with an ID that stays the same across retries, so receivers can drop duplicates.
+pub const DELIVERY_HEADER: &str = "X-Terrier-Delivery";
+
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Where to send the result of a finished run.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Environment variable that holds the shared secret, so the secret itself never has to
+    /// be written into a config file or result.
+    #[serde(default = "default_secret_env")]
+    pub secret_env: String,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_secret_env() -> String {
+    "TERRIER_WEBHOOK_SECRET".to_string()
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+impl WebhookConfig {
+    pub fn secret(&self) -> Option<String> {
+        std::env::var(&self.secret_env)
+            .ok()
+            .filter(|secret| !secret.is_empty())
+    }
+}
+
+/// `sha256=<hex digest>` of `body` keyed with `secret`.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+/// Client errors other than timeouts and rate limits will not go away by sending the same
+/// request again.
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+async fn post_with_retries(
+    config: &WebhookConfig,
+    secret: &[u8],
+    body: Vec<u8>,
+    first_delay: Duration,
+) -> Result<u32, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|err| err.to_string())?;
+    let signature = sign(secret, &body);
+    let delivery = uuid::Uuid::new_v4().to_string();
+    let attempts = config.max_attempts.max(1);
+    let mut delay = first_delay;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+        let response = client
+            .post(&config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .header(DELIVERY_HEADER, &delivery)
+            .body(body.clone())
+            .send()
+            .await;
+        let error = match response {
+            Ok(response) if response.status().is_success() => return Ok(attempt),
+            Ok(response) if !is_retryable(response.status()) => {
+                return Err(format!(
+                    "the receiver rejected it with {}",
+                    response.status()
+                ));
+            }
+            Ok(response) => format!("the receiver answered {}", response.status()),
+            Err(err) => err.to_string(),
+        };
+        if attempt >= attempts {
+            return Err(format!("{} after {} attempt(s)", error, attempts));
+        }
+        warn!(
+            attempt,
+            "Webhook delivery failed ({}); retrying in {:?}", error, delay
+        );
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
+    }
+}
+
+/// POSTs `payload` as JSON to the webhook, signed with the shared secret. Failed attempts
+/// are retried with exponential backoff.
+pub async fn deliver<T: Serialize>(config: &WebhookConfig, payload: &T) -> Result<(), String> {
+    let secret = config
+        .secret()
+        .ok_or_else(|| format!("the secret variable {} is not set", config.secret_env))?;
+    let body = serde_json::to_vec(payload).map_err(|err| err.to_string())?;
+    let attempts = post_with_retries(config, secret.as_bytes(), body, FIRST_RETRY_DELAY).await?;
+    info!(url = %config.url, attempts, "Delivered results to webhook");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use std::sync::{Arc, Mutex};
+
+    /// Starts a receiver that answers with `statuses` in turn and records what it got.
+    async fn receiver(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<(HeaderMap, Vec<u8>)>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: axum::body::Bytes| {
+                let log = log.clone();
+                let statuses = statuses.clone();
+                async move {
+                    let mut log = log.lock().unwrap();
+                    log.push((headers, body.to_vec()));
+                    let status = statuses[(log.len() - 1).min(statuses.len() - 1)];
+                    axum::http::StatusCode::from_u16(status).unwrap()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, received)
+    }
+
+    fn config(url: String, max_attempts: u32) -> WebhookConfig {
+        WebhookConfig {
+            url,
+            secret_env: default_secret_env(),
+            max_attempts,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery_is_retried_until_accepted() {
+        let (url, received) = receiver(vec![503, 500, 204]).await;
+        let body = br#"{"outcome":{"verdict":"Verified"}}"#.to_vec();
+        let attempts =
+            post_with_retries(&config(url, 5), b"secret", body.clone(), Duration::ZERO).await;
+        assert_eq!(attempts, Ok(3));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        let (headers, payload) = &received[2];
+        assert_eq!(payload, &body);
+        assert_eq!(headers[SIGNATURE_HEADER], sign(b"secret", &body));
+        assert_eq!(headers[DELIVERY_HEADER], received[0].0[DELIVERY_HEADER]);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_and_exhausted_attempts_fail() {
+        let (url, received) = receiver(vec![401]).await;
+        let err = post_with_retries(&config(url, 5), b"secret", Vec::new(), Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.contains("401"));
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        let (url, received) = receiver(vec![502]).await;
+        let err = post_with_retries(&config(url, 2), b"secret", Vec::new(), Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.contains("2 attempt(s)"));
+        assert_eq!(received.lock().unwrap().len(), 2);
+    }
+}
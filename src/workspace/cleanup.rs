@@ -0,0 +1,149 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Paths to delete if the process is interrupted before their owners are dropped: whole
+/// directories for temporary workspaces, lock files for persistent ones.
+static PENDING_CLEANUP: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+pub fn register(path: &Path) {
+    if let Ok(mut pending) = PENDING_CLEANUP.lock() {
+        pending.push(path.to_path_buf());
+    }
+}
+
+pub fn unregister(path: &Path) {
+    if let Ok(mut pending) = PENDING_CLEANUP.lock() {
+        pending.retain(|p| p != path);
+    }
+}
+
+fn remove_path(path: &Path) {
+    if path.is_dir() {
+        let _ = fs::remove_dir_all(path);
+    } else {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Installs a Ctrl-C handler that removes every registered path and exits with status 130.
+/// Drop handlers do not run when the process is killed by a signal, so this is what keeps
+/// an interrupted run from leaving clones and stale locks behind.
+pub fn install_interrupt_handler() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if let Ok(mut pending) = PENDING_CLEANUP.lock() {
+            for path in pending.drain(..) {
+                remove_path(&path);
+            }
+        }
+        warn!("Interrupted; temporary workspaces and locks were removed");
+        std::process::exit(130);
+    })
+}
+
+/// Exclusive lock on a directory, held by creating `<dir>/.lock` containing our PID.
+/// A lock left behind by a process that no longer exists is taken over.
+#[derive(Debug)]
+pub struct DirLock {
+    path: PathBuf,
+}
+
+fn process_is_running(pid: u32) -> bool {
+    let proc_root = Path::new("/proc");
+    if proc_root.exists() {
+        proc_root.join(pid.to_string()).exists()
+    } else {
+        // Without procfs there is no portable liveness check; assume the holder is alive.
+        true
+    }
+}
+
+impl DirLock {
+    pub fn acquire(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(".lock");
+        for _ in 0..2 {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    register(&path);
+                    return Ok(Self { path });
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|pid| pid.trim().parse::<u32>().ok());
+                    match holder {
+                        Some(pid) if !process_is_running(pid) => {
+                            warn!(
+                                "Removing stale lock {} left by process {}",
+                                path.display(),
+                                pid
+                            );
+                            let _ = fs::remove_file(&path);
+                        }
+                        _ => {
+                            return Err(format!(
+                                "'{}' is in use by another run (process {}). If no other run is active, delete '{}'.",
+                                dir.display(),
+                                holder
+                                    .map(|p| p.to_string())
+                                    .unwrap_or_else(|| "unknown".into()),
+                                path.display()
+                            ));
+                        }
+                    }
+                }
+                Err(err) => {
+                    return Err(format!("Failed to lock '{}': {}", dir.display(), err));
+                }
+            }
+        }
+        Err(format!("Failed to lock '{}'.", dir.display()))
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        unregister(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_temp_dir() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("lock_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_and_released_on_drop() {
+        let dir = unique_temp_dir();
+        let lock = DirLock::acquire(&dir).unwrap();
+        assert!(DirLock::acquire(&dir).is_err());
+        drop(lock);
+        assert!(!dir.join(".lock").exists());
+        assert!(DirLock::acquire(&dir).is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let dir = unique_temp_dir();
+        // PIDs are bounded well below u32::MAX on every supported platform.
+        fs::write(dir.join(".lock"), u32::MAX.to_string()).unwrap();
+        if Path::new("/proc").exists() {
+            assert!(DirLock::acquire(&dir).is_ok());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
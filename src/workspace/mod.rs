@@ -0,0 +1,263 @@
+pub mod cleanup;
+
+use crate::git_tools::github_issue::GithubIssue;
+use crate::git_tools::metadata::MetadataVerificationResult;
+use crate::git_tools::repository::GithubRepo;
+use crate::plag_check::assets::AssetCheckResult;
+use crate::plag_check::plag_result::PlagiarismVerificationResult;
+use cleanup::DirLock;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub const SUBMISSION_STATE: &str = "submission.json";
+pub const FETCH_STATE: &str = "fetch.json";
+pub const METADATA_STATE: &str = "metadata.json";
+pub const PLAGIARISM_STATE: &str = "plagiarism.json";
+
+/// Output of the `fetch` stage: the comparison repositories cloned for copydetect.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FetchState {
+    pub references: Vec<GithubRepo>,
+    /// Local directories of the event's boilerplate, excluded from similarity.
+    #[serde(default)]
+    pub boilerplate: Vec<String>,
+    pub github_issues: Vec<GithubIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataState {
+    pub metadata: MetadataVerificationResult,
+    pub github_issues: Vec<GithubIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlagiarismState {
+    pub plagiarism: PlagiarismVerificationResult,
+    pub assets: AssetCheckResult,
+}
+
+/// Directory shared by the pipeline stages. Clones and stage results are kept here so each
+/// stage can be rerun on its own:
+///
+/// ```text
+/// <root>/submission/   clone of the submitted repository
+/// <root>/references/   shallow clones of the comparison repositories
+/// <root>/boilerplate/  shallow clones of the event's boilerplate repositories
+/// <root>/copydetect/   copydetect working directory and report
+/// <root>/state/        JSON results of each stage
+/// ```
+///
+/// A workspace is locked while open, so two runs never share one. Temporary workspaces are
+/// deleted when dropped (including during a panic) unless `keep` was requested.
+pub struct Workspace {
+    root: PathBuf,
+    temporary: bool,
+    keep: bool,
+    _lock: DirLock,
+}
+
+/// Where temporary workspaces are created and whether they survive the run.
+#[derive(Debug, Clone)]
+pub struct WorkdirOptions {
+    pub root: PathBuf,
+    pub keep: bool,
+}
+
+impl Workspace {
+    /// Opens (creating if needed) a persistent workspace at `root`.
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(root.as_ref())?;
+        let root = fs::canonicalize(root)?;
+        let lock = DirLock::acquire(&root)?;
+        Ok(Self {
+            root,
+            temporary: false,
+            keep: true,
+            _lock: lock,
+        })
+    }
+
+    /// Creates a uniquely named workspace under `options.root` for a single run.
+    pub fn temporary(options: &WorkdirOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        let root = options
+            .root
+            .join(format!("terrier_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root)?;
+        let root = fs::canonicalize(root)?;
+        if !options.keep {
+            cleanup::register(&root);
+        }
+        let lock = DirLock::acquire(&root)?;
+        Ok(Self {
+            root,
+            temporary: true,
+            keep: options.keep,
+            _lock: lock,
+        })
+    }
+
+    pub fn submission_dir(&self) -> PathBuf {
+        self.root.join("submission")
+    }
+
+    pub fn references_dir(&self) -> PathBuf {
+        self.root.join("references")
+    }
+
+    pub fn boilerplate_dir(&self) -> PathBuf {
+        self.root.join("boilerplate")
+    }
+
+    pub fn copydetect_dir(&self) -> PathBuf {
+        self.root.join("copydetect")
+    }
+
+    fn state_path(&self, name: &str) -> PathBuf {
+        self.root.join("state").join(name)
+    }
+
+    pub fn save_state<T: Serialize>(
+        &self,
+        name: &str,
+        state: &T,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.state_path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+
+    pub fn load_state<T: DeserializeOwned>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>, Box<dyn std::error::Error>> {
+        let path = self.state_path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path)?;
+        let state = serde_json::from_str(&contents).map_err(|err| {
+            format!(
+                "The stage result '{}' is corrupt or from an incompatible version: {}",
+                path.display(),
+                err
+            )
+        })?;
+        Ok(Some(state))
+    }
+
+    /// Loads a stage result that a later stage depends on, pointing the user at the stage
+    /// to run when it is missing.
+    pub fn require_state<T: DeserializeOwned>(
+        &self,
+        name: &str,
+        stage: &str,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        self.load_state(name)?.ok_or_else(|| {
+            format!(
+                "The workspace '{}' has no {} results yet. Run the `{}` subcommand first.",
+                self.root.display(),
+                stage,
+                stage
+            )
+            .into()
+        })
+    }
+
+    /// Returns the clone of `url` in this workspace, cloning it first if there is none or if
+    /// the existing clone is of a different repository. `refresh` forces a new clone.
+    pub fn submission(&self, url: &str, refresh: bool) -> Result<GithubRepo, git2::Error> {
+        let existing: Option<GithubRepo> = self.load_state(SUBMISSION_STATE).ok().flatten();
+        if let Some(repo) = existing
+            && !refresh
+            && repo.url == url
+            && Path::new(&repo.local_path).join(".git").exists()
+        {
+            return Ok(repo);
+        }
+
+        let path = self.submission_dir();
+        let _ = fs::remove_dir_all(&path);
+        let repo = GithubRepo::new_with_local_path(url, &path.display().to_string(), false)?;
+        if let Err(err) = self.save_state(SUBMISSION_STATE, &repo) {
+            warn!("Failed to record submission clone in workspace: {}", err);
+        }
+        Ok(repo)
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if !self.temporary {
+            return;
+        }
+        if self.keep {
+            info!("Keeping workspace at {}", self.root.display());
+        } else {
+            let _ = fs::remove_dir_all(&self.root);
+            cleanup::unregister(&self.root);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip_and_missing_stage() {
+        let root = std::env::temp_dir().join(format!("workspace_test_{}", uuid::Uuid::new_v4()));
+        let workspace = Workspace::open(&root).unwrap();
+
+        let missing = workspace.require_state::<FetchState>(FETCH_STATE, "fetch");
+        assert!(missing.unwrap_err().to_string().contains("`fetch`"));
+
+        let state = FetchState {
+            references: vec![GithubRepo {
+                url: "https://github.com/a/b".to_string(),
+                local_path: "/tmp/x".to_string(),
+            }],
+            boilerplate: Vec::new(),
+            github_issues: vec![GithubIssue::path_not_found("Boilerplate directory", "x")],
+        };
+        workspace.save_state(FETCH_STATE, &state).unwrap();
+        let loaded: FetchState = workspace.require_state(FETCH_STATE, "fetch").unwrap();
+        assert_eq!(loaded.references[0].url, "https://github.com/a/b");
+        assert_eq!(loaded.github_issues, state.github_issues);
+
+        drop(workspace);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_temporary_workspaces_are_unique_and_removed_on_drop() {
+        let options = WorkdirOptions {
+            root: std::env::temp_dir(),
+            keep: false,
+        };
+        let first = Workspace::temporary(&options).unwrap();
+        let second = Workspace::temporary(&options).unwrap();
+        assert_ne!(first.root, second.root);
+
+        let root = first.root.clone();
+        fs::create_dir_all(first.submission_dir()).unwrap();
+        drop(first);
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_persistent_workspace_is_locked_and_kept() {
+        let root = std::env::temp_dir().join(format!("workspace_test_{}", uuid::Uuid::new_v4()));
+        let workspace = Workspace::open(&root).unwrap();
+        assert!(Workspace::open(&root).is_err());
+        drop(workspace);
+        assert!(root.exists());
+        assert!(Workspace::open(&root).is_ok());
+        let _ = fs::remove_dir_all(&root);
+    }
+}